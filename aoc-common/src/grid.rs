@@ -0,0 +1,351 @@
+use crate::ParseError;
+use std::ops::{Index, IndexMut};
+
+// A rectangular grid stored row by row. Positions are `(x, y)` with `x` the
+// column and `y` the row, both counted from the top left.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+const OFFSETS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    // `None` if the rows are not all the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Grid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    // One row per line and one cell per character. `cell` turns a character
+    // into a cell, or gives `None` to reject it with `expected` as the error.
+    pub fn parse<F>(input: &[String], expected: &str, mut cell: F) -> Result<Grid<T>, ParseError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut rows = Vec::new();
+        let width = input.first().map_or(0, |line| line.chars().count());
+
+        for (y, line) in input.iter().enumerate() {
+            let mut row = Vec::with_capacity(width);
+            for (offset, c) in line.char_indices() {
+                let token = &line[offset..offset + c.len_utf8()];
+                if row.len() == width {
+                    let expected = format!("a line {} characters long", width);
+                    return Err(ParseError::new(line, token, &expected).on_line(y));
+                }
+                row.push(cell(c).ok_or_else(|| ParseError::new(line, token, expected).on_line(y))?);
+            }
+            if row.len() != width {
+                return Err(ParseError::end_of_line(
+                    line,
+                    &format!("a line {} characters long", width),
+                )
+                .on_line(y));
+            }
+            rows.push(row);
+        }
+
+        Ok(Grid::from_rows(rows).unwrap())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.contains(x, y).then(|| &self.cells[y * self.width + x])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        Some(&mut self.cells[y * self.width + x])
+    }
+
+    fn offsets(
+        &self,
+        x: usize,
+        y: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        offsets.iter().filter_map(move |&(dx, dy)| {
+            let x = x.checked_add_signed(dx)?;
+            let y = y.checked_add_signed(dy)?;
+            self.contains(x, y).then_some((x, y))
+        })
+    }
+
+    // Positions above, left, right and below that are inside the grid.
+    pub fn neighbours_4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(x, y, &OFFSETS_4)
+    }
+
+    // As `neighbours_4` but with the diagonals too, in reading order.
+    pub fn neighbours_8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(x, y, &OFFSETS_8)
+    }
+
+    // Every cell with its position, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| ((i % self.width, i / self.width), cell))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on 0, an empty grid has no cells to split anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    // Rows become columns, so `(x, y)` moves to `(y, x)`.
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flatten().cloned().collect(),
+        }
+    }
+
+    // A quarter turn clockwise, the first column read bottom up becomes the
+    // first row.
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for x in 0..self.width {
+            for y in (0..self.height).rev() {
+                cells.push(self[(x, y)].clone());
+            }
+        }
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for x in (0..self.width).rev() {
+            for y in 0..self.height {
+                cells.push(self[(x, y)].clone());
+            }
+        }
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        match self.get(x, y) {
+            Some(cell) => cell,
+            None => panic!(
+                "position ({}, {}) is outside a {}x{} grid",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(x, y) {
+            Some(cell) => cell,
+            None => panic!(
+                "position ({}, {}) is outside a {}x{} grid",
+                x, y, width, height
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|r| r.to_string()).collect()
+    }
+
+    fn sample() -> Grid<char> {
+        Grid::parse(&lines(&["abc", "def"]), "a letter", Some).unwrap()
+    }
+
+    fn text(grid: &Grid<char>) -> Vec<String> {
+        grid.rows().map(|row| row.iter().collect()).collect()
+    }
+
+    #[test]
+    fn parse_and_index() {
+        let grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside a 3x2 grid")]
+    fn index_out_of_bounds() {
+        let _ = sample()[(0, 2)];
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Grid::parse(&lines(&["ab", "a1"]), "a letter", |c| {
+            c.is_alphabetic().then_some(c)
+        })
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.expected, "a letter");
+
+        let error = Grid::parse(&lines(&["ab", "a"]), "a letter", Some).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.expected, "a line 2 characters long");
+
+        let error = Grid::parse(&lines(&["ab", "abc"]), "a letter", Some).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(error.found, "c");
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = sample();
+        assert_eq!(
+            grid.neighbours_4(0, 0).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours_8(1, 1).collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)]
+        );
+        assert_eq!(grid.neighbours_8(2, 0).count(), 3);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = sample();
+        assert_eq!(text(&grid), vec!["abc", "def"]);
+        let columns = grid
+            .columns()
+            .map(|c| c.collect::<String>())
+            .collect::<Vec<String>>();
+        assert_eq!(columns, vec!["ad", "be", "cf"]);
+        assert_eq!(grid.column(3).count(), 0);
+    }
+
+    #[test]
+    fn transpose_and_rotate() {
+        let grid = sample();
+        assert_eq!(text(&grid.transpose()), vec!["ad", "be", "cf"]);
+        assert_eq!(text(&grid.rotate_clockwise()), vec!["da", "eb", "fc"]);
+        assert_eq!(
+            text(&grid.rotate_counterclockwise()),
+            vec!["cf", "be", "ad"]
+        );
+        assert_eq!(grid.transpose().transpose(), grid);
+        let turned = (0..4).fold(grid.clone(), |g, _| g.rotate_clockwise());
+        assert_eq!(turned, grid);
+    }
+
+    #[test]
+    fn empty() {
+        let grid: Grid<char> = Grid::parse(&[], "a letter", Some).unwrap();
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        assert_eq!(grid.rows().count(), 0);
+        assert_eq!(grid.iter().count(), 0);
+    }
+
+    #[test]
+    fn iter_positions() {
+        let grid = sample();
+        let mut copy = Grid::new(3, 2, ' ');
+        for ((x, y), &c) in grid.iter() {
+            copy[(x, y)] = c;
+        }
+        assert_eq!(copy, grid);
+        assert_eq!(
+            grid.map(|c| c.is_ascii_uppercase())
+                .iter()
+                .filter(|(_, &u)| u)
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::time::{Duration, Instant};
+
+pub mod grid;
+pub mod rng;
+
+pub use grid::Grid;
+pub use rng::Rng;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub found: String,
+    pub expected: String,
+    pub source: String,
+}
+
+impl ParseError {
+    // `found` should be a slice of `source` so the column can be worked out,
+    // anything else is reported as the end of the line.
+    pub fn new(source: &str, found: &str, expected: &str) -> ParseError {
+        let offset = (found.as_ptr() as usize)
+            .checked_sub(source.as_ptr() as usize)
+            .filter(|offset| offset + found.len() <= source.len())
+            .unwrap_or(source.len());
+
+        ParseError {
+            line: 1,
+            column: source[..offset].chars().count() + 1,
+            found: found.to_string(),
+            expected: expected.to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    pub fn end_of_line(source: &str, expected: &str) -> ParseError {
+        ParseError::new(source, &source[source.len()..], expected)
+    }
+
+    // `index` counts from 0, as given by `enumerate`.
+    pub fn on_line(mut self, index: usize) -> ParseError {
+        self.line = index + 1;
+        self
+    }
+
+    // A rustc style report pointing at the offending text.
+    pub fn render(&self, filename: &str) -> String {
+        let gutter = " ".repeat(self.line.to_string().len());
+        let marker = "^".repeat(self.found.chars().count().max(1));
+        format!(
+            "error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{} expected {}",
+            self,
+            gutter,
+            filename,
+            self.line,
+            self.column,
+            gutter,
+            self.line,
+            self.source,
+            gutter,
+            " ".repeat(self.column - 1),
+            marker,
+            self.expected
+        )
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let found = if self.found.is_empty() {
+            "end of line".to_string()
+        } else {
+            format!("`{}`", self.found)
+        };
+        write!(f, "expected {}, found {}", self.expected, found)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlankLines {
+    // Keep every blank line, for inputs where line numbers carry meaning.
+    Keep,
+    // Squash runs of blank lines into one, for inputs split into sections.
+    Collapse,
+    // Remove every blank line, for inputs with one record per line.
+    Drop,
+}
+
+pub trait Solution {
+    type Parsed;
+    type Answer: Display;
+
+    const BLANK_LINES: BlankLines = BlankLines::Keep;
+
+    fn parse(&self, input: &[String]) -> Result<Self::Parsed, ParseError>;
+    fn part_1(&self, parsed: &Self::Parsed) -> Self::Answer;
+    fn part_2(&self, parsed: &Self::Parsed) -> Self::Answer;
+}
+
+pub fn run<S: Solution>(solution: &S, filename: &str) {
+    let input = read_input(filename, S::BLANK_LINES);
+    let parsed = match solution.parse(&input) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("{}", error.render(filename));
+            std::process::exit(1);
+        }
+    };
+
+    print_result(1, solution.part_1(&parsed));
+    print_result(2, solution.part_2(&parsed));
+}
+
+#[derive(Debug, Clone)]
+pub struct PartResult {
+    pub part: usize,
+    pub answer: String,
+    pub elapsed: Duration,
+}
+
+// The object safe side of `Solution`, so days with different answer types can
+// sit in one list.
+pub trait Runnable {
+    fn blank_lines(&self) -> BlankLines;
+    fn run_parts(&self, input: &[String], parts: &[usize]) -> Result<Vec<PartResult>, ParseError>;
+}
+
+impl<S: Solution> Runnable for S {
+    fn blank_lines(&self) -> BlankLines {
+        S::BLANK_LINES
+    }
+
+    fn run_parts(&self, input: &[String], parts: &[usize]) -> Result<Vec<PartResult>, ParseError> {
+        let parsed = self.parse(input)?;
+
+        Ok(parts
+            .iter()
+            .filter_map(|&part| {
+                let start = Instant::now();
+                let answer = match part {
+                    1 => self.part_1(&parsed),
+                    2 => self.part_2(&parsed),
+                    _ => return None,
+                };
+                Some(PartResult {
+                    part,
+                    answer: answer.to_string(),
+                    elapsed: start.elapsed(),
+                })
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    args: Vec<String>,
+    // Flags that stand alone rather than taking the next argument as a value.
+    switches: Vec<String>,
+}
+
+impl Args {
+    pub fn new(args: Vec<String>) -> Args {
+        Args {
+            args,
+            switches: Vec::new(),
+        }
+    }
+
+    pub fn from_env() -> Args {
+        Args::new(env::args().skip(1).collect())
+    }
+
+    // Names the flags that never take a value, any other `--name` followed by
+    // an argument is read as `--name value`.
+    pub fn switches(mut self, names: &[&str]) -> Args {
+        self.switches = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    // The arguments that are neither flags nor their values, wherever they are.
+    pub fn positional(&self) -> Vec<&str> {
+        let mut positional = Vec::new();
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if !name.contains('=') && !self.switches.iter().any(|s| s == name) => {
+                    args.next();
+                }
+                Some(_) => {}
+                None => positional.push(arg.as_str()),
+            }
+        }
+        positional
+    }
+
+    // The first positional argument, or `None` after asking for one.
+    pub fn input_path(&self) -> Option<String> {
+        match self.positional().first() {
+            Some(path) => Some(path.to_string()),
+            None => {
+                println!("Please provide a filename");
+                None
+            }
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.args.iter().any(|a| a == &format!("--{}", name))
+    }
+
+    // Accepts both `--name value` and `--name=value`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        let flag = format!("--{}", name);
+        let prefix = format!("--{}=", name);
+        for (i, arg) in self.args.iter().enumerate() {
+            if arg == &flag {
+                return self.args.get(i + 1).map(|a| a.as_str());
+            }
+            if let Some(value) = arg.strip_prefix(&prefix) {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+pub fn input_path() -> Option<String> {
+    Args::from_env().input_path()
+}
+
+// Strips a byte order mark, turns `\r\n` and lone `\r` into `\n`, blanks out
+// lines holding only whitespace and drops blank lines from the end.
+pub fn normalize(contents: &str, blank_lines: BlankLines) -> Vec<String> {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let contents = contents.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in contents.split('\n') {
+        let line = if line.trim().is_empty() { "" } else { line };
+        let skip = match blank_lines {
+            BlankLines::Keep => false,
+            BlankLines::Collapse => line.is_empty() && lines.last().is_none_or(|l| l.is_empty()),
+            BlankLines::Drop => line.is_empty(),
+        };
+        if !skip {
+            lines.push(line.to_string());
+        }
+    }
+
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+pub fn read_input(filename: &str, blank_lines: BlankLines) -> Vec<String> {
+    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
+    normalize(&contents, blank_lines)
+}
+
+pub fn print_result<T: Display>(part: usize, result: T) {
+    println!("Part {} -> {}", part, result);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_line_endings() {
+        let result = normalize("\u{feff}a\r\nb\rc\n", BlankLines::Keep);
+        assert_eq!(result, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn normalize_keep() {
+        let result = normalize("\na\n\n \nb\n\n\n", BlankLines::Keep);
+        assert_eq!(result, vec!["", "a", "", "", "b"]);
+    }
+
+    #[test]
+    fn normalize_collapse() {
+        let result = normalize("\na\r\n\r\n \r\nb\r\n\r\n", BlankLines::Collapse);
+        assert_eq!(result, vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn normalize_drop() {
+        let result = normalize("a\n\n\t\nb\n", BlankLines::Drop);
+        assert_eq!(result, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn parse_error_column() {
+        let line = "Game x: 3 blue";
+        let error = ParseError::new(line, &line[5..6], "a game id").on_line(2);
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 6);
+        assert_eq!(error.to_string(), "expected a game id, found `x`");
+    }
+
+    #[test]
+    fn parse_error_end_of_line() {
+        let error = ParseError::end_of_line("Game 1", "`:`");
+        assert_eq!(error.column, 7);
+        assert_eq!(error.to_string(), "expected `:`, found end of line");
+    }
+
+    #[test]
+    fn parse_error_render() {
+        let line = "Game 1: 3 blew";
+        let error = ParseError::new(line, &line[10..], "a colour").on_line(11);
+        assert_eq!(
+            error.render("input.txt"),
+            "error: expected a colour, found `blew`\n  --> input.txt:12:11\n   |\n12 | Game 1: 3 blew\n   |           ^^^^ expected a colour"
+        );
+    }
+
+    fn args(args: &[&str]) -> Args {
+        Args::new(args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn args_positional() {
+        let args = args(&["run", "extra", "--day", "3"]);
+        assert_eq!(args.positional(), vec!["run", "extra"]);
+    }
+
+    #[test]
+    fn args_positional_after_flags() {
+        let args = args(&["--part", "2", "--input=x.txt", "--all", "run"]);
+        assert_eq!(args.positional(), Vec::<&str>::new());
+        let args = args.switches(&["all"]);
+        assert_eq!(args.positional(), vec!["run"]);
+        assert_eq!(args.input_path(), Some("run".to_string()));
+        assert_eq!(args.value("part"), Some("2"));
+    }
+
+    #[test]
+    fn args_values() {
+        let args = args(&["run", "--day", "3", "--part=2", "--all"]);
+        assert_eq!(args.value("day"), Some("3"));
+        assert_eq!(args.value("part"), Some("2"));
+        assert_eq!(args.value("input"), None);
+        assert!(args.flag("all"));
+        assert!(!args.flag("day-3"));
+    }
+}
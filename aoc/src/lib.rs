@@ -0,0 +1,142 @@
+use aoc_common::{Args, Runnable};
+
+pub struct Day {
+    pub number: usize,
+    pub input: &'static str,
+    pub solution: Box<dyn Runnable>,
+}
+
+pub fn registry() -> Vec<Day> {
+    vec![
+        Day {
+            number: 1,
+            input: "day-1/puzzle.txt",
+            solution: Box::new(day_1::Day1),
+        },
+        Day {
+            number: 2,
+            input: "day-2/input.txt",
+            solution: Box::new(day_2::Day2::default()),
+        },
+        Day {
+            number: 3,
+            input: "day-3/input.txt",
+            solution: Box::new(day_3::Day3),
+        },
+        Day {
+            number: 4,
+            input: "day-4/input.txt",
+            solution: Box::new(day_4::Day4),
+        },
+        Day {
+            number: 5,
+            input: "day-5/input.txt",
+            solution: Box::new(day_5::Day5),
+        },
+    ]
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run {
+        day: usize,
+        parts: Vec<usize>,
+        input: Option<String>,
+    },
+    RunAll {
+        parts: Vec<usize>,
+    },
+}
+
+pub const USAGE: &str =
+    "Usage: aoc run --day <n> [--part <1|2>] [--input <path>]\n       aoc run --all [--part <1|2>]";
+
+pub fn parse_command(args: &Args) -> Result<Command, String> {
+    match args.positional().first() {
+        Some(&"run") => {}
+        Some(other) => return Err(format!("Unknown command `{}`", other)),
+        None => return Err("Please provide a command".to_string()),
+    }
+
+    let parts = match args.value("part") {
+        Some("1") => vec![1],
+        Some("2") => vec![2],
+        Some(other) => return Err(format!("Unknown part `{}`", other)),
+        None => vec![1, 2],
+    };
+
+    if args.flag("all") {
+        return Ok(Command::RunAll { parts });
+    }
+
+    let day = args
+        .value("day")
+        .ok_or("Please provide --day or --all")?
+        .parse::<usize>()
+        .map_err(|_| "--day must be a number".to_string())?;
+
+    Ok(Command::Run {
+        day,
+        parts,
+        input: args.value("input").map(|s| s.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Args {
+        Args::new(args.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn run_one_part() {
+        let command = parse_command(&args(&[
+            "run", "--day", "3", "--part", "2", "--input", "x.txt",
+        ]));
+        assert_eq!(
+            command,
+            Ok(Command::Run {
+                day: 3,
+                parts: vec![2],
+                input: Some("x.txt".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn run_all() {
+        let command = parse_command(&args(&["run", "--all"]));
+        assert_eq!(command, Ok(Command::RunAll { parts: vec![1, 2] }));
+    }
+
+    #[test]
+    fn bad_commands() {
+        assert!(parse_command(&args(&[])).is_err());
+        assert!(parse_command(&args(&["walk", "--all"])).is_err());
+        assert!(parse_command(&args(&["run"])).is_err());
+        assert!(parse_command(&args(&["run", "--day", "three"])).is_err());
+        assert!(parse_command(&args(&["run", "--all", "--part", "3"])).is_err());
+    }
+
+    #[test]
+    fn registry_days() {
+        let days = registry();
+        assert_eq!(
+            days.iter().map(|d| d.number).collect::<Vec<usize>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn registry_runs_day() {
+        let day = registry().into_iter().find(|d| d.number == 4).unwrap();
+        let input = vec!["Card 1: 31 18 13 56 72 | 74 77 10 23 35 67 36 11".to_string()];
+        let results = day.solution.run_parts(&input, &[1, 2]).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].answer, "0");
+        assert_eq!(results[1].answer, "1");
+    }
+}
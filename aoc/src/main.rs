@@ -0,0 +1,64 @@
+use aoc::{parse_command, registry, Command, Day, USAGE};
+use aoc_common::{read_input, Args};
+use std::path::Path;
+use std::process;
+
+fn main() {
+    let command = match parse_command(&Args::from_env().switches(&["all"])) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            process::exit(1);
+        }
+    };
+
+    let days = registry();
+
+    let ok = match command {
+        Command::Run { day, parts, input } => {
+            let Some(day) = days.iter().find(|d| d.number == day) else {
+                eprintln!("No solution registered for day {}", day);
+                process::exit(1);
+            };
+            let input = input.unwrap_or(day.input.to_string());
+            run_day(day, &input, &parts)
+        }
+        // Keeps going past a failing day, so the others still get their answers.
+        Command::RunAll { parts } => {
+            let mut ok = true;
+            for day in days.iter() {
+                ok &= run_day(day, day.input, &parts);
+            }
+            ok
+        }
+    };
+    if !ok {
+        process::exit(1);
+    }
+}
+
+// `false` if the input is missing or doesn't parse.
+fn run_day(day: &Day, input: &str, parts: &[usize]) -> bool {
+    if !Path::new(input).exists() {
+        eprintln!("Day {}: no input at {}", day.number, input);
+        return false;
+    }
+
+    let data = read_input(input, day.solution.blank_lines());
+
+    let results = match day.solution.run_parts(&data, parts) {
+        Ok(results) => results,
+        Err(error) => {
+            eprintln!("{}", error.render(input));
+            return false;
+        }
+    };
+
+    for result in results {
+        println!(
+            "Day {} Part {} -> {} ({:.2?})",
+            day.number, result.part, result.answer, result.elapsed
+        );
+    }
+    true
+}
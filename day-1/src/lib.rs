@@ -5,7 +5,7 @@ pub mod part_1 {
             .filter_map(|c| c.to_digit(10))
             .collect::<Vec<u32>>();
 
-        if nums_in_line.len() == 0 {
+        if nums_in_line.is_empty() {
             return 0;
         }
 
@@ -15,7 +15,7 @@ pub mod part_1 {
         first * 10 + last
     }
 
-    pub fn find_calibration_value(input: &Vec<String>) -> u32 {
+    pub fn find_calibration_value(input: &[String]) -> u32 {
         let mut result = 0;
 
         for line in input.iter() {
@@ -42,42 +42,63 @@ pub mod part_2 {
         for i in 0..chars.len() {
             let slice = slice_to_string(&chars[i..]);
             if slice.starts_with("one") {
-                result.push_str("1");
+                result.push('1');
             } else if slice.starts_with("two") {
-                result.push_str("2");
+                result.push('2');
             } else if slice.starts_with("three") {
-                result.push_str("3");
+                result.push('3');
             } else if slice.starts_with("four") {
-                result.push_str("4");
+                result.push('4');
             } else if slice.starts_with("five") {
-                result.push_str("5");
+                result.push('5');
             } else if slice.starts_with("six") {
-                result.push_str("6");
+                result.push('6');
             } else if slice.starts_with("seven") {
-                result.push_str("7");
+                result.push('7');
             } else if slice.starts_with("eight") {
-                result.push_str("8");
+                result.push('8');
             } else if slice.starts_with("nine") {
-                result.push_str("9");
-            } else if chars[i].is_digit(10) {
+                result.push('9');
+            } else if chars[i].is_ascii_digit() {
                 result.push(chars[i]);
             }
         }
         result
     }
 
-    pub fn find_calibration_value(input: &Vec<String>) -> u32 {
-        let input: Vec<String> = input.iter().map(|l| replace_didgets(&l)).collect();
+    pub fn find_calibration_value(input: &[String]) -> u32 {
+        let input: Vec<String> = input.iter().map(|l| replace_didgets(l)).collect();
         let mut result = 0;
 
         for line in input.iter() {
-            result += super::part_1::handel_line(&line);
+            result += super::part_1::handel_line(line);
         }
 
         result
     }
 }
 
+pub struct Day1;
+
+impl aoc_common::Solution for Day1 {
+    type Parsed = Vec<String>;
+    type Answer = u32;
+
+    const BLANK_LINES: aoc_common::BlankLines = aoc_common::BlankLines::Drop;
+
+    fn parse(&self, input: &[String]) -> Result<Vec<String>, aoc_common::ParseError> {
+        Ok(input.to_vec())
+    }
+
+    fn part_1(&self, parsed: &Vec<String>) -> u32 {
+        part_1::find_calibration_value(parsed)
+    }
+
+    fn part_2(&self, parsed: &Vec<String>) -> u32 {
+        part_2::find_calibration_value(parsed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     mod part_1 {
@@ -85,7 +106,7 @@ mod tests {
 
         #[test]
         fn sample() {
-            let input = vec!["1abc2", "pqr3stu8vwx", "a1b2c3d4e5f", "treb7uchet"]
+            let input = ["1abc2", "pqr3stu8vwx", "a1b2c3d4e5f", "treb7uchet"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -96,7 +117,7 @@ mod tests {
 
         #[test]
         fn none() {
-            let input = vec!["trebuchet"]
+            let input = ["trebuchet"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -107,7 +128,7 @@ mod tests {
 
         #[test]
         fn one() {
-            let input = vec!["treb7uchet"]
+            let input = ["treb7uchet"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -118,7 +139,7 @@ mod tests {
 
         #[test]
         fn two_single() {
-            let input = vec!["pqr3stu8vwx"]
+            let input = ["pqr3stu8vwx"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -129,7 +150,7 @@ mod tests {
 
         #[test]
         fn two_many() {
-            let input = vec!["1abc2", "pqr3stu8vwx"]
+            let input = ["1abc2", "pqr3stu8vwx"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -140,7 +161,7 @@ mod tests {
 
         #[test]
         fn many_many() {
-            let input = vec!["a1b2c3d4e5f", "a1b2c34e5f"]
+            let input = ["a1b2c3d4e5f", "a1b2c34e5f"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -155,7 +176,7 @@ mod tests {
 
         #[test]
         fn sample() {
-            let input = vec![
+            let input = [
                 "two1nine",
                 "eightwothree",
                 "abcone2threexyz",
@@ -182,7 +203,7 @@ mod tests {
 
         #[test]
         fn none() {
-            let input = vec!["trebuchet"]
+            let input = ["trebuchet"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -193,7 +214,7 @@ mod tests {
 
         #[test]
         fn one_num() {
-            let input = vec!["treb7uchet"]
+            let input = ["treb7uchet"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -204,7 +225,7 @@ mod tests {
 
         #[test]
         fn single_duplicate() {
-            let input = vec!["threeightwothree"]
+            let input = ["threeightwothree"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -215,7 +236,7 @@ mod tests {
 
         #[test]
         fn one() {
-            let input = vec!["one"]
+            let input = ["one"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -226,7 +247,7 @@ mod tests {
 
         #[test]
         fn overlap() {
-            let input = vec!["oneight"]
+            let input = ["oneight"]
                 .iter()
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>();
@@ -0,0 +1,262 @@
+use crate::{Game, Round};
+use aoc_common::ParseError;
+
+fn parse_entry(line: &str, entry: &str, separator: char) -> Result<(String, usize), ParseError> {
+    let (color, count) = entry
+        .split_once(separator)
+        .ok_or_else(|| ParseError::new(line, entry, &format!("`<color> {} <count>`", separator)))?;
+    let color = color.trim().trim_matches('"');
+    let count = count.trim();
+
+    if color.is_empty() || !color.chars().all(char::is_alphabetic) {
+        return Err(ParseError::new(line, color, "a color name"));
+    }
+    let count = count
+        .parse::<usize>()
+        .map_err(|_| ParseError::new(line, count, "a number"))?;
+
+    Ok((color.to_string(), count))
+}
+
+// A bag given on the command line, such as `red=12,green=13,blue=14`.
+pub fn parse_bag(spec: &str) -> Result<Round, ParseError> {
+    let mut bag = Round::new();
+    for entry in spec.split(",") {
+        let (color, count) = parse_entry(spec, entry.trim(), '=')?;
+        bag.set(&color, count);
+    }
+    Ok(bag)
+}
+
+// A bag read from a file, either a flat TOML table (`red = 12` per line) or
+// a flat JSON object (`{"red": 12, "green": 13}`).
+pub fn parse_bag_config(contents: &str) -> Result<Round, ParseError> {
+    let json = contents.trim_start().starts_with('{');
+    let mut bag = Round::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let entries: Vec<&str> = if json {
+            line.split(",").collect()
+        } else {
+            vec![line.split("#").next().unwrap()]
+        };
+
+        for entry in entries {
+            let entry = entry.trim();
+            let entry = if json {
+                entry.trim_start_matches('{').trim_end_matches('}').trim()
+            } else {
+                entry
+            };
+            if entry.is_empty() || (!json && entry.starts_with('[')) {
+                continue;
+            }
+
+            let separator = if json { ':' } else { '=' };
+            let (color, count) = parse_entry(line, entry, separator).map_err(|e| e.on_line(i))?;
+            bag.set(&color, count);
+        }
+    }
+    Ok(bag)
+}
+
+// More bags than this would take too long to check, and fill the screen anyway.
+const MAX_SWEEP_BAGS: usize = 100_000;
+
+// Counts to try for each color, such as `red=10..=14,green=13,blue=12..15`.
+pub fn parse_sweep(spec: &str) -> Result<Vec<(String, Vec<usize>)>, ParseError> {
+    let mut axes = Vec::new();
+    let mut bags: usize = 1;
+
+    for entry in spec.split(",") {
+        let entry = entry.trim();
+        let (color, counts) = entry
+            .split_once('=')
+            .ok_or_else(|| ParseError::new(spec, entry, "`<color>=<range>`"))?;
+        if color.is_empty() || !color.chars().all(char::is_alphabetic) {
+            return Err(ParseError::new(spec, color, "a color name"));
+        }
+
+        let number = |s: &str| {
+            s.parse::<usize>()
+                .map_err(|_| ParseError::new(spec, s, "a number"))
+        };
+        // Both ends inclusive, so a count of `usize::MAX` still fits.
+        let (start, end) = if let Some((start, end)) = counts.split_once("..=") {
+            (number(start)?, Some(number(end)?))
+        } else if let Some((start, end)) = counts.split_once("..") {
+            (number(start)?, number(end)?.checked_sub(1))
+        } else {
+            let count = number(counts)?;
+            (count, Some(count))
+        };
+        let end = end
+            .filter(|&end| start <= end)
+            .ok_or_else(|| ParseError::new(spec, counts, "a range that isn't empty"))?;
+        bags = bags.saturating_mul((end - start).saturating_add(1));
+        if bags > MAX_SWEEP_BAGS {
+            let expected = format!("ranges that make at most {} bags", MAX_SWEEP_BAGS);
+            return Err(ParseError::new(spec, counts, &expected));
+        }
+
+        axes.push((color.to_string(), (start..=end).collect()));
+    }
+    Ok(axes)
+}
+
+// Every combination of the given counts, with how many games each bag allows.
+pub fn sweep(games: &[Game], axes: &[(String, Vec<usize>)]) -> Vec<(Round, usize)> {
+    let mut bags = vec![Round::new()];
+    for (color, counts) in axes {
+        bags = bags
+            .iter()
+            .flat_map(|bag| {
+                counts.iter().map(move |&count| {
+                    let mut bag = bag.clone();
+                    bag.set(color, count);
+                    bag
+                })
+            })
+            .collect();
+    }
+
+    bags.into_iter()
+        .map(|bag| {
+            let valid = games.iter().filter(|g| g.check_game(&bag)).count();
+            (bag, valid)
+        })
+        .collect()
+}
+
+pub fn sweep_table(axes: &[(String, Vec<usize>)], rows: &[(Round, usize)]) -> String {
+    let mut headers = axes.iter().map(|(c, _)| c.as_str()).collect::<Vec<&str>>();
+    headers.push("valid");
+    let widths = headers
+        .iter()
+        .map(|h| h.len().max(5))
+        .collect::<Vec<usize>>();
+
+    let mut table = headers
+        .iter()
+        .zip(&widths)
+        .map(|(h, w)| format!("{:>w$}", h, w = w))
+        .collect::<Vec<String>>()
+        .join(" ");
+    table.push('\n');
+
+    for (bag, valid) in rows {
+        let mut cells = headers[..headers.len() - 1]
+            .iter()
+            .map(|c| bag.get(c))
+            .collect::<Vec<usize>>();
+        cells.push(*valid);
+        let row = cells
+            .iter()
+            .zip(&widths)
+            .map(|(n, w)| format!("{:>w$}", n, w = w))
+            .collect::<Vec<String>>()
+            .join(" ");
+        table.push_str(&row);
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_game;
+    use crate::fixtures::round;
+
+    #[test]
+    fn bag_from_args() {
+        assert_eq!(
+            parse_bag("red=12,green=13,blue=14").unwrap(),
+            round(12, 13, 14)
+        );
+        assert_eq!(parse_bag("red=12, yellow=2").unwrap().get("yellow"), 2);
+
+        let error = parse_bag("red=12,green=x").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (14, "x"));
+        let error = parse_bag("red=12,green").unwrap_err();
+        assert_eq!(error.found, "green");
+    }
+
+    #[test]
+    fn bag_from_toml() {
+        let config = "# limits\n[bag]\nred = 12\ngreen = 13 # more\n\"blue\" = 14\n";
+        assert_eq!(parse_bag_config(config).unwrap(), round(12, 13, 14));
+
+        let error = parse_bag_config("red = 12\ngreen: 13\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+    }
+
+    #[test]
+    fn bag_from_json() {
+        let config = "{\n  \"red\": 12,\n  \"green\": 13, \"blue\": 14\n}\n";
+        assert_eq!(parse_bag_config(config).unwrap(), round(12, 13, 14));
+        assert_eq!(parse_bag_config("{\"red\": 1}").unwrap(), round(1, 0, 0));
+
+        let error = parse_bag_config("{\n  \"red\": twelve\n}").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 10));
+    }
+
+    #[test]
+    fn sweep_ranges() {
+        let axes = parse_sweep("red=1..=2,green=5,blue=0..2").unwrap();
+        assert_eq!(
+            axes,
+            vec![
+                ("red".to_string(), vec![1, 2]),
+                ("green".to_string(), vec![5]),
+                ("blue".to_string(), vec![0, 1]),
+            ]
+        );
+        assert!(parse_sweep("red=1..x").is_err());
+    }
+
+    #[test]
+    fn sweep_rejects_bad_ranges() {
+        let error = parse_sweep("green=2,red=5..3").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (13, "5..3"));
+        assert_eq!(error.expected, "a range that isn't empty");
+
+        let error = parse_sweep("red=4..4").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (5, "4..4"));
+        assert!(parse_sweep("red=4..=4").is_ok());
+        assert!(parse_sweep("red=5..=3").is_err());
+        assert!(parse_sweep("red=0..0").is_err());
+        assert_eq!(
+            parse_sweep("red=18446744073709551615").unwrap(),
+            vec![("red".to_string(), vec![usize::MAX])]
+        );
+
+        let error = parse_sweep("red=0..=99999999999").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (5, "0..=99999999999"));
+        assert_eq!(error.expected, "ranges that make at most 100000 bags");
+
+        let error = parse_sweep("red=0..1000,green=0..1000").unwrap_err();
+        assert_eq!(error.found, "0..1000");
+        assert_eq!(error.column, 19);
+    }
+
+    #[test]
+    fn sweep_counts_valid_games() {
+        let games = vec![
+            build_game("Game 1: 1 red, 2 green").unwrap(),
+            build_game("Game 2: 2 red; 1 blue").unwrap(),
+        ];
+        let axes = parse_sweep("red=1..=2,green=2,blue=0..=1").unwrap();
+        let rows = sweep(&games, &axes);
+
+        assert_eq!(
+            rows.iter().map(|(_, valid)| *valid).collect::<Vec<usize>>(),
+            vec![1, 1, 1, 2]
+        );
+        assert_eq!(rows[3].0, round(2, 2, 1));
+        assert_eq!(
+            sweep_table(&axes, &rows[..1]),
+            "  red green  blue valid\n    1     2     0     1\n"
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{Game, Round};
+use aoc_common::ParseError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+// Colors and `total` only mean something inside a round, the rest can be used
+// anywhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Number(usize),
+    Color(String),
+    Total,
+    Id,
+    Rounds,
+    Max(Box<Value>),
+    Min(Box<Value>),
+    Sum(Box<Value>),
+    Count(Box<Filter>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Compare(Value, Op, Value),
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Any(Box<Filter>),
+    All(Box<Filter>),
+}
+
+impl Op {
+    fn apply(self, left: usize, right: usize) -> bool {
+        match self {
+            Op::Lt => left < right,
+            Op::Le => left <= right,
+            Op::Gt => left > right,
+            Op::Ge => left >= right,
+            Op::Eq => left == right,
+            Op::Ne => left != right,
+        }
+    }
+}
+
+impl Value {
+    fn per_round<'a>(&'a self, game: &'a Game) -> impl Iterator<Item = usize> + 'a {
+        game.rounds.iter().map(move |r| self.eval(game, Some(r)))
+    }
+
+    fn eval(&self, game: &Game, round: Option<&Round>) -> usize {
+        match self {
+            Value::Number(n) => *n,
+            Value::Color(color) => round.map_or(0, |r| r.get(color)),
+            Value::Total => round.map_or(0, Round::total),
+            Value::Id => game.id,
+            Value::Rounds => game.rounds.len(),
+            Value::Max(value) => value.per_round(game).max().unwrap_or(0),
+            Value::Min(value) => value.per_round(game).min().unwrap_or(0),
+            Value::Sum(value) => value.per_round(game).sum(),
+            Value::Count(filter) => game
+                .rounds
+                .iter()
+                .filter(|r| filter.eval(game, Some(r)))
+                .count(),
+        }
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, game: &Game) -> bool {
+        self.eval(game, None)
+    }
+
+    fn eval(&self, game: &Game, round: Option<&Round>) -> bool {
+        match self {
+            Filter::Compare(left, op, right) => {
+                op.apply(left.eval(game, round), right.eval(game, round))
+            }
+            Filter::Not(filter) => !filter.eval(game, round),
+            Filter::And(a, b) => a.eval(game, round) && b.eval(game, round),
+            Filter::Or(a, b) => a.eval(game, round) || b.eval(game, round),
+            Filter::Any(filter) => game.rounds.iter().any(|r| filter.eval(game, Some(r))),
+            Filter::All(filter) => game.rounds.iter().all(|r| filter.eval(game, Some(r))),
+        }
+    }
+}
+
+// These can't be used as color names in a filter.
+const KEYWORDS: [&str; 12] = [
+    "and", "or", "not", "any", "all", "count", "max", "min", "sum", "total", "id", "rounds",
+];
+
+fn tokenize(source: &str) -> Result<Vec<&str>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_digit() {
+            rest.find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len())
+        } else if c.is_alphabetic() {
+            rest.find(|c: char| !c.is_alphabetic())
+                .unwrap_or(rest.len())
+        } else if ["<=", ">=", "==", "!="]
+            .iter()
+            .any(|op| rest.starts_with(op))
+        {
+            2
+        } else if "()<>=".contains(c) {
+            1
+        } else {
+            let found = &rest[..c.len_utf8()];
+            return Err(ParseError::new(
+                source,
+                found,
+                "a color, number, operator or bracket",
+            ));
+        };
+        tokens.push(&rest[..len]);
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<&'a str>,
+    next: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.next).copied()
+    }
+
+    fn error(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError::new(self.source, token, expected),
+            None => ParseError::end_of_line(self.source, expected),
+        }
+    }
+
+    fn bump(&mut self, expected: &str) -> Result<&'a str, ParseError> {
+        let token = self.peek().ok_or_else(|| self.error(expected))?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.peek() != Some(token) {
+            return Err(self.error(&format!("`{}`", token)));
+        }
+        self.next += 1;
+        Ok(())
+    }
+
+    fn or(&mut self, in_round: bool) -> Result<Filter, ParseError> {
+        let mut filter = self.and(in_round)?;
+        while self.peek() == Some("or") {
+            self.next += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.and(in_round)?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self, in_round: bool) -> Result<Filter, ParseError> {
+        let mut filter = self.not(in_round)?;
+        while self.peek() == Some("and") {
+            self.next += 1;
+            filter = Filter::And(Box::new(filter), Box::new(self.not(in_round)?));
+        }
+        Ok(filter)
+    }
+
+    fn not(&mut self, in_round: bool) -> Result<Filter, ParseError> {
+        if self.peek() == Some("not") {
+            self.next += 1;
+            return Ok(Filter::Not(Box::new(self.not(in_round)?)));
+        }
+        self.atom(in_round)
+    }
+
+    // Parses `(<round condition>)` after `any`, `all` or `count`.
+    fn round_filter(&mut self) -> Result<Box<Filter>, ParseError> {
+        self.expect("(")?;
+        let filter = self.or(true)?;
+        self.expect(")")?;
+        Ok(Box::new(filter))
+    }
+
+    fn atom(&mut self, in_round: bool) -> Result<Filter, ParseError> {
+        match self.peek() {
+            Some("(") => {
+                self.next += 1;
+                let filter = self.or(in_round)?;
+                self.expect(")")?;
+                Ok(filter)
+            }
+            Some(quantifier @ ("any" | "all")) => {
+                if in_round {
+                    return Err(self.error("a condition on this round"));
+                }
+                self.next += 1;
+                let filter = self.round_filter()?;
+                Ok(if quantifier == "any" {
+                    Filter::Any(filter)
+                } else {
+                    Filter::All(filter)
+                })
+            }
+            _ => {
+                let left = self.value(in_round)?;
+                let op = match self.peek() {
+                    Some("<") => Op::Lt,
+                    Some("<=") => Op::Le,
+                    Some(">") => Op::Gt,
+                    Some(">=") => Op::Ge,
+                    Some("=" | "==") => Op::Eq,
+                    Some("!=") => Op::Ne,
+                    _ => return Err(self.error("a comparison")),
+                };
+                self.next += 1;
+                let right = self.value(in_round)?;
+                Ok(Filter::Compare(left, op, right))
+            }
+        }
+    }
+
+    fn value(&mut self, in_round: bool) -> Result<Value, ParseError> {
+        let start = self.next;
+        let token = self.bump("a value")?;
+        let game_value = "a game value such as `max(blue)` or `rounds`";
+        let round_value = "a value of this round such as `blue` or `total`";
+
+        let value = match token {
+            "id" => Value::Id,
+            "rounds" => Value::Rounds,
+            "total" if in_round => Value::Total,
+            aggregate @ ("max" | "min" | "sum") if !in_round => {
+                self.expect("(")?;
+                let value = Box::new(self.value(true)?);
+                self.expect(")")?;
+                match aggregate {
+                    "max" => Value::Max(value),
+                    "min" => Value::Min(value),
+                    _ => Value::Sum(value),
+                }
+            }
+            "count" if !in_round => Value::Count(self.round_filter()?),
+            _ if token.starts_with(|c: char| c.is_ascii_digit()) => Value::Number(
+                token
+                    .parse::<usize>()
+                    .map_err(|_| ParseError::new(self.source, token, "a number"))?,
+            ),
+            _ if token.starts_with(char::is_alphabetic) && !KEYWORDS.contains(&token) => {
+                if !in_round {
+                    self.next = start;
+                    return Err(self.error(game_value));
+                }
+                Value::Color(token.to_string())
+            }
+            _ => {
+                self.next = start;
+                return Err(self.error(if in_round { round_value } else { game_value }));
+            }
+        };
+        Ok(value)
+    }
+}
+
+// A filter such as `any(blue > 10) and sum(red) < 20`.
+//
+// At the top level a filter talks about the whole game: `id`, `rounds`,
+// `max(..)`, `min(..)` and `sum(..)` of a round value, and `count(..)` of the
+// rounds matching a condition. Inside `any(..)`, `all(..)` and `count(..)` the
+// condition is about a single round, where a color gives its count and
+// `total` all cubes shown. Conditions combine with `and`, `or`, `not` and
+// brackets, and compare with `<`, `<=`, `>`, `>=`, `==` and `!=`.
+pub fn parse_filter(spec: &str) -> Result<Filter, ParseError> {
+    let mut parser = Parser {
+        source: spec,
+        tokens: tokenize(spec)?,
+        next: 0,
+    };
+    let filter = parser.or(false)?;
+    if parser.peek().is_some() {
+        return Err(parser.error("`and`, `or` or the end of the filter"));
+    }
+    Ok(filter)
+}
+
+pub fn filter_games<'a>(games: &'a [Game], filter: &Filter) -> Vec<&'a Game> {
+    games.iter().filter(|game| filter.matches(game)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample;
+
+    fn ids(spec: &str) -> Vec<usize> {
+        let filter = parse_filter(spec).unwrap();
+        filter_games(&sample(), &filter)
+            .iter()
+            .map(|g| g.id)
+            .collect()
+    }
+
+    #[test]
+    fn quantifiers() {
+        assert_eq!(ids("any(blue > 10)"), vec![4]);
+        assert_eq!(ids("all(total <= 10)"), vec![1, 2, 5]);
+        assert_eq!(ids("any(red > 0 and green > 0 and blue == 0)"), vec![3, 4]);
+    }
+
+    #[test]
+    fn aggregates() {
+        assert!(ids("any(blue > 10) and sum(red) < 20").is_empty());
+        assert_eq!(ids("any(blue > 5) and sum(red) < 20"), vec![1]);
+        assert_eq!(ids("max(red) >= 14"), vec![3, 4]);
+        assert_eq!(ids("min(total) == 2"), vec![1, 2]);
+        assert_eq!(ids("count(red == 0) >= 2"), vec![2]);
+        assert_eq!(ids("sum(total) > 40 or id = 2"), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(ids("id < 2 or id > 3 and rounds == 2"), vec![1, 5]);
+        assert_eq!(ids("(id < 2 or id > 3) and rounds == 2"), vec![5]);
+        assert_eq!(ids("not id == 1 and not (id > 2)"), vec![2]);
+        assert_eq!(ids("all(not (total > 10))"), vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn missing_colors_are_zero() {
+        assert_eq!(
+            ids("any(purple == 0) and max(purple) == 0"),
+            vec![1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn color_outside_round() {
+        let error = parse_filter("any(blue > 1) and red > 2").unwrap_err();
+        assert_eq!(error.column, 19);
+        assert_eq!(error.found, "red");
+        assert!(error.expected.starts_with("a game value"));
+    }
+
+    #[test]
+    fn nested_quantifier() {
+        let error = parse_filter("any(any(red > 1))").unwrap_err();
+        assert_eq!(error.column, 5);
+        assert_eq!(error.found, "any");
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse_filter("any(blue > 1").unwrap_err();
+        assert_eq!(error.column, 13);
+        assert_eq!(error.expected, "`)`");
+
+        let error = parse_filter("rounds > 1 rounds").unwrap_err();
+        assert_eq!(error.column, 12);
+
+        let error = parse_filter("rounds ~ 1").unwrap_err();
+        assert_eq!(error.column, 8);
+        assert_eq!(error.found, "~");
+
+        let error = parse_filter("sum(red) <").unwrap_err();
+        assert_eq!(error.expected, "a value");
+    }
+}
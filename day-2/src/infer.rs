@@ -0,0 +1,237 @@
+use crate::part_2::find_minimum_cubes;
+use crate::{colors, Game, Round};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorChange {
+    pub color: String,
+    pub from: usize,
+    pub to: usize,
+    pub extra_games: usize,
+}
+
+// The smallest bag every game fits in. A bag is consistent with all of the
+// games exactly when it dominates this one.
+pub fn minimum_bag(games: &[Game]) -> Round {
+    games
+        .iter()
+        .fold(Round::new(), |acc, game| acc.max(&find_minimum_cubes(game)))
+}
+
+pub fn is_consistent(bag: &Round, games: &[Game]) -> bool {
+    games.iter().all(|game| game.check_game(bag))
+}
+
+// The bag with the fewest cubes in total that at least `k` games fit in.
+//
+// Each color of the best bag matches some game's minimum for that color, so
+// only those counts are tried. The last color is then picked directly as the
+// `k`th smallest need among the games that fit the other colors.
+pub fn smallest_bag_for(games: &[Game], k: usize) -> Option<Round> {
+    if k > games.len() {
+        return None;
+    }
+    let colors = colors(games);
+    let needs = games.iter().map(find_minimum_cubes).collect::<Vec<Round>>();
+    let candidates = colors
+        .iter()
+        .map(|color| {
+            let mut counts = needs.iter().map(|n| n.get(color)).collect::<Vec<usize>>();
+            counts.push(0);
+            counts.sort();
+            counts.dedup();
+            counts
+        })
+        .collect::<Vec<Vec<usize>>>();
+
+    let mut best: Option<(usize, Vec<usize>)> = None;
+    let fitting = (0..needs.len()).collect::<Vec<usize>>();
+    search(
+        &colors,
+        &candidates,
+        &needs,
+        k,
+        &fitting,
+        &mut vec![],
+        0,
+        &mut best,
+    );
+
+    best.map(|(_, counts)| {
+        colors
+            .iter()
+            .zip(counts)
+            .map(|(color, count)| (color.as_str(), count))
+            .collect()
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search(
+    colors: &[String],
+    candidates: &[Vec<usize>],
+    needs: &[Round],
+    k: usize,
+    fitting: &[usize],
+    chosen: &mut Vec<usize>,
+    cost: usize,
+    best: &mut Option<(usize, Vec<usize>)>,
+) {
+    let depth = chosen.len();
+    if depth == colors.len() {
+        if best.as_ref().is_none_or(|(b, _)| cost < *b) {
+            *best = Some((cost, chosen.clone()));
+        }
+        return;
+    }
+
+    if depth + 1 == colors.len() {
+        let mut last = fitting
+            .iter()
+            .map(|&g| needs[g].get(&colors[depth]))
+            .collect::<Vec<usize>>();
+        last.sort();
+        let count = if k == 0 { 0 } else { last[k - 1] };
+        chosen.push(count);
+        search(
+            colors,
+            candidates,
+            needs,
+            k,
+            fitting,
+            chosen,
+            cost + count,
+            best,
+        );
+        chosen.pop();
+        return;
+    }
+
+    for &count in &candidates[depth] {
+        if best.as_ref().is_some_and(|(b, _)| cost + count >= *b) {
+            break;
+        }
+        let still_fitting = fitting
+            .iter()
+            .copied()
+            .filter(|&g| needs[g].get(&colors[depth]) <= count)
+            .collect::<Vec<usize>>();
+        if still_fitting.len() < k {
+            continue;
+        }
+        chosen.push(count);
+        search(
+            colors,
+            candidates,
+            needs,
+            k,
+            &still_fitting,
+            chosen,
+            cost + count,
+            best,
+        );
+        chosen.pop();
+    }
+}
+
+// Raising one color of `bag` at a time, the change that lets the most extra
+// games fit, using the smallest raise that gets there. `None` if no single
+// color helps.
+pub fn best_color_change(games: &[Game], bag: &Round) -> Option<ColorChange> {
+    let valid = games.iter().filter(|g| g.check_game(bag)).count();
+    let needs = games.iter().map(find_minimum_cubes).collect::<Vec<Round>>();
+    let mut best: Option<ColorChange> = None;
+
+    for color in colors(games) {
+        let from = bag.get(&color);
+        let mut counts = needs
+            .iter()
+            .map(|n| n.get(&color))
+            .filter(|&n| n > from)
+            .collect::<Vec<usize>>();
+        counts.sort();
+        counts.dedup();
+
+        for to in counts {
+            let mut raised = bag.clone();
+            raised.set(&color, to);
+            let extra_games = games.iter().filter(|g| g.check_game(&raised)).count() - valid;
+
+            let better = match &best {
+                None => extra_games > 0,
+                Some(b) => {
+                    extra_games > b.extra_games
+                        || (extra_games == b.extra_games && to - from < b.to - b.from)
+                }
+            };
+            if better {
+                best = Some(ColorChange {
+                    color: color.clone(),
+                    from,
+                    to,
+                    extra_games,
+                });
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{round, sample};
+
+    #[test]
+    fn consistent_bags() {
+        let games = sample();
+        assert_eq!(minimum_bag(&games), round(20, 13, 15));
+        assert!(is_consistent(&round(20, 13, 15), &games));
+        assert!(is_consistent(&round(25, 13, 20), &games));
+        assert!(!is_consistent(&round(19, 13, 15), &games));
+    }
+
+    #[test]
+    fn smallest_bags() {
+        let games = sample();
+        assert_eq!(smallest_bag_for(&games, 5), Some(round(20, 13, 15)));
+        assert_eq!(smallest_bag_for(&games, 1).map(|b| b.total()), Some(8));
+        assert_eq!(smallest_bag_for(&games, 0).map(|b| b.total()), Some(0));
+        assert_eq!(smallest_bag_for(&games, 6), None);
+    }
+
+    #[test]
+    fn smallest_bags_match_brute_force() {
+        let games = sample();
+        for k in 0..=games.len() {
+            let brute = (0..1usize << games.len())
+                .filter(|mask| mask.count_ones() as usize >= k)
+                .map(|mask| {
+                    games
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| mask & (1 << i) != 0)
+                        .fold(Round::new(), |acc, (_, g)| acc.max(&find_minimum_cubes(g)))
+                        .total()
+                })
+                .min();
+            let found = smallest_bag_for(&games, k).unwrap();
+            assert_eq!(Some(found.total()), brute);
+            assert!(games.iter().filter(|g| g.check_game(&found)).count() >= k);
+        }
+    }
+
+    #[test]
+    fn color_change() {
+        let games = sample();
+        assert_eq!(
+            best_color_change(&games, &round(12, 13, 14)),
+            Some(ColorChange {
+                color: "red".to_string(),
+                from: 12,
+                to: 20,
+                extra_games: 1,
+            })
+        );
+        assert_eq!(best_color_change(&games, &round(20, 13, 15)), None);
+    }
+}
@@ -1,51 +1,237 @@
-#[derive(Debug, Default)]
+use aoc_common::{BlankLines, ParseError};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod bag;
+pub mod filter;
+pub mod infer;
+pub mod simulate;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Game {
     pub id: usize,
     pub rounds: Vec<Round>,
 }
 
-#[derive(Debug, Default, PartialEq, PartialOrd)]
+// Cube counts by color, kept in the order they were first given. Colors that
+// are missing count as 0.
+#[derive(Debug, Default, Clone)]
 pub struct Round {
-    pub red: usize,
-    pub green: usize,
-    pub blue: usize,
+    cubes: Vec<(String, usize)>,
+}
+
+// Rounds are compared one color at a time, so a round is only less than
+// another if no color has more cubes. Rounds that differ both ways have no order.
+impl Round {
+    pub fn new() -> Round {
+        Round::default()
+    }
+
+    pub fn get(&self, color: &str) -> usize {
+        self.cubes
+            .iter()
+            .find(|(c, _)| c == color)
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    }
+
+    pub fn set(&mut self, color: &str, count: usize) {
+        match self.cubes.iter_mut().find(|(c, _)| c == color) {
+            Some(entry) => entry.1 = count,
+            None => self.cubes.push((color.to_string(), count)),
+        }
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.cubes.iter().map(|(c, _)| c.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.cubes.iter().map(|(c, count)| (c.as_str(), *count))
+    }
+
+    pub fn total(&self) -> usize {
+        self.cubes.iter().map(|(_, count)| count).sum()
+    }
+
+    // The product of the counts of `colors`, any of them missing makes it 0.
+    pub fn power(&self, colors: &[&str]) -> usize {
+        colors.iter().map(|c| self.get(c)).product()
+    }
+
+    pub fn fits_within(&self, bag: &Round) -> bool {
+        self.iter().all(|(color, count)| count <= bag.get(color))
+    }
+
+    pub fn dominates(&self, other: &Round) -> bool {
+        other.fits_within(self)
+    }
+
+    pub fn max(&self, other: &Round) -> Round {
+        let mut result = self.clone();
+        for (color, count) in other.iter() {
+            result.set(color, self.get(color).max(count));
+        }
+        result
+    }
+
+    pub fn min(&self, other: &Round) -> Round {
+        let mut result = self.clone();
+        for (color, count) in self.iter() {
+            result.set(color, count.min(other.get(color)));
+        }
+        for color in other.colors() {
+            result.set(color, self.get(color).min(other.get(color)));
+        }
+        result
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<(S, usize)> for Round {
+    fn from_iter<I: IntoIterator<Item = (S, usize)>>(iter: I) -> Round {
+        let mut round = Round::new();
+        for (color, count) in iter {
+            round.set(color.as_ref(), count);
+        }
+        round
+    }
+}
+
+impl PartialEq for Round {
+    fn eq(&self, other: &Round) -> bool {
+        self.fits_within(other) && other.fits_within(self)
+    }
+}
+
+impl Eq for Round {}
+
+impl PartialOrd for Round {
+    fn partial_cmp(&self, other: &Round) -> Option<Ordering> {
+        match (self.fits_within(other), other.fits_within(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+// Every color that shows up in any round, in the order first seen.
+pub fn colors(games: &[Game]) -> Vec<String> {
+    let mut colors: Vec<String> = Vec::new();
+    for color in games
+        .iter()
+        .flat_map(|g| g.rounds.iter().flat_map(|r| r.colors()))
+    {
+        if !colors.iter().any(|c| c == color) {
+            colors.push(color.to_string());
+        }
+    }
+    colors
 }
 
-pub fn build_game(game: &str) -> Option<Game> {
-    let id = game
-        .split(":")
-        .nth(0)?
-        .split(" ")
-        .nth(1)?
+fn parse_count(line: &str, token: &str) -> Result<usize, ParseError> {
+    token
         .parse::<usize>()
-        .unwrap_or(0);
-    let rounds = game
-        .split(":")
-        .nth(1)
-        .unwrap()
+        .map_err(|_| ParseError::new(line, token, "a number"))
+}
+
+// Parses one `<count> <color>, ...` round out of `line`, which is only used to
+// place errors.
+fn parse_round(line: &str, round: &str) -> Result<Round, ParseError> {
+    let mut result = Round::new();
+
+    for color in round.split(",") {
+        let color = color.trim();
+        let (count, name) = color
+            .split_once(" ")
+            .ok_or_else(|| ParseError::new(line, color, "`<count> <color>`"))?;
+        let count = parse_count(line, count)?;
+
+        if name.is_empty() || !name.chars().all(char::is_alphabetic) {
+            return Err(ParseError::new(line, name, "a color name"));
+        }
+        if result.colors().any(|c| c == name) {
+            return Err(ParseError::new(
+                line,
+                name,
+                "a color not already in this round",
+            ));
+        }
+        result.set(name, count);
+    }
+
+    Ok(result)
+}
+
+pub fn build_game(game: &str) -> Result<Game, ParseError> {
+    let (head, body) = game
+        .split_once(":")
+        .ok_or_else(|| ParseError::end_of_line(game, "`:`"))?;
+    let id = head
+        .strip_prefix("Game ")
+        .ok_or_else(|| ParseError::new(game, head, "`Game <id>`"))?;
+    let id = parse_count(game, id.trim())?;
+
+    let rounds = body
         .split(";")
-        .map(|round| {
-            let mut red = 0;
-            let mut green = 0;
-            let mut blue = 0;
-
-            round.split(",").for_each(|color| {
-                let color = color.trim();
-
-                if color.contains("red") {
-                    red = color.split(" ").nth(0).unwrap().parse::<usize>().unwrap();
-                } else if color.contains("green") {
-                    green = color.split(" ").nth(0).unwrap().parse::<usize>().unwrap();
-                } else if color.contains("blue") {
-                    blue = color.split(" ").nth(0).unwrap().parse::<usize>().unwrap();
-                }
-            });
+        .map(|round| parse_round(game, round))
+        .collect::<Result<Vec<Round>, ParseError>>()?;
+
+    Ok(Game { id, rounds })
+}
+
+// Written back out in the puzzle's own syntax, so `to_string` and `parse` undo
+// each other for any game that has at least one round.
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (color, count)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", count, color)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        for (i, round) in self.rounds.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", round)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Round {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Round, ParseError> {
+        parse_round(s, s)
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParseError;
 
-            Round { red, green, blue }
-        })
-        .collect::<Vec<Round>>();
+    fn from_str(s: &str) -> Result<Game, ParseError> {
+        build_game(s)
+    }
+}
 
-    Some(Game { id, rounds })
+pub fn parse_input(input: &[String]) -> Result<Vec<Game>, ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| build_game(line).map_err(|e| e.on_line(i)))
+        .collect()
 }
 
 pub mod part_1 {
@@ -53,89 +239,207 @@ pub mod part_1 {
 
     impl Game {
         pub fn check_game(&self, total: &Round) -> bool {
-            for round in &self.rounds {
-                if round > total {
-                    return false;
-                }
-            }
-
-            true
+            self.rounds.iter().all(|round| round.fits_within(total))
         }
     }
 
-    pub fn sum_of_invalid_games(input: &Vec<String>, total: &Round) -> usize {
-        input
+    pub fn sum_of_valid_game_ids(games: &[Game], total: &Round) -> usize {
+        games
             .iter()
-            .map(|game| {
-                build_game(game).unwrap_or(Game {
-                    id: 0,
-                    rounds: vec![],
-                })
-            })
             .filter(|game| game.check_game(total))
             .map(|game| game.id)
             .sum::<usize>()
     }
+
+    pub fn sum_of_invalid_games(input: &[String], total: &Round) -> usize {
+        sum_of_valid_game_ids(&parse_input(input).unwrap(), total)
+    }
 }
 
 pub mod part_2 {
     use super::*;
 
-    impl Round {
-        fn combine(self, other: &Round) -> Round {
-            Round {
-                red: std::cmp::max(self.red, other.red),
-                green: std::cmp::max(self.green, other.green),
-                blue: std::cmp::max(self.blue, other.blue),
-            }
-        }
-    }
-
     pub fn find_minimum_cubes(game: &Game) -> Round {
-        game.rounds.iter().fold(Round::default(), Round::combine)
+        game.rounds
+            .iter()
+            .fold(Round::default(), |acc, round| acc.max(round))
     }
 
-    pub fn sum_of_power(input: &Vec<String>) -> usize {
-        input
+    pub fn sum_of_game_powers(games: &[Game]) -> usize {
+        let colors = colors(games);
+        let colors = colors.iter().map(|c| c.as_str()).collect::<Vec<&str>>();
+        games
             .iter()
-            .map(|game| {
-                build_game(game).unwrap_or_default()
-            })
-            .map(|game| {
-                let minimum = find_minimum_cubes(&game);
-                minimum.red * minimum.green * minimum.blue
-            })
+            .map(|game| find_minimum_cubes(game).power(&colors))
             .sum::<usize>()
     }
+
+    pub fn sum_of_power(input: &[String]) -> usize {
+        sum_of_game_powers(&parse_input(input).unwrap())
+    }
+}
+
+pub struct Day2 {
+    pub bag: Round,
+}
+
+impl Default for Day2 {
+    fn default() -> Self {
+        Day2 {
+            bag: Round::from_iter([("red", 12), ("green", 13), ("blue", 14)]),
+        }
+    }
+}
+
+impl aoc_common::Solution for Day2 {
+    type Parsed = Vec<Game>;
+    type Answer = usize;
+
+    const BLANK_LINES: BlankLines = BlankLines::Drop;
+
+    fn parse(&self, input: &[String]) -> Result<Vec<Game>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(&self, parsed: &Vec<Game>) -> usize {
+        part_1::sum_of_valid_game_ids(parsed, &self.bag)
+    }
+
+    fn part_2(&self, parsed: &Vec<Game>) -> usize {
+        part_2::sum_of_game_powers(parsed)
+    }
+}
+
+// Inputs shared by the tests of every module.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::{parse_input, Game, Round};
+
+    pub(crate) fn round(red: usize, green: usize, blue: usize) -> Round {
+        Round::from_iter([("red", red), ("green", green), ("blue", blue)])
+    }
+
+    // The games from the puzzle description, as lines and parsed.
+    pub(crate) fn sample_input() -> Vec<String> {
+        [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+            "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+            "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    pub(crate) fn sample() -> Vec<Game> {
+        parse_input(&sample_input()).unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::fixtures::round;
+
+    #[test]
+    fn fits_within_each_color() {
+        let bag = round(12, 13, 14);
+        assert!(round(12, 13, 14).fits_within(&bag));
+        assert!(round(0, 0, 0).fits_within(&bag));
+        assert!(!round(1, 0, 99).fits_within(&bag));
+        assert!(!round(12, 14, 0).fits_within(&bag));
+    }
+
+    #[test]
+    fn lexicographic_disagreement() {
+        // Lexicographically (1, 0, 99) < (12, 13, 14), but it has too much blue.
+        let bag = round(12, 13, 14);
+        let greedy = round(1, 0, 99);
+        assert!(!greedy.fits_within(&bag));
+        assert_eq!(greedy.partial_cmp(&bag), None);
+
+        let game = Game {
+            id: 1,
+            rounds: vec![greedy],
+        };
+        assert!(!game.check_game(&bag));
+    }
+
+    #[test]
+    fn partial_order() {
+        assert_eq!(
+            round(1, 2, 3).partial_cmp(&round(1, 2, 3)),
+            Some(Ordering::Equal)
+        );
+        assert!(round(1, 2, 3) < round(1, 5, 3));
+        assert!(round(4, 2, 3) > round(1, 2, 3));
+        assert!(round(4, 2, 3).dominates(&round(1, 2, 3)));
+        assert!(!round(4, 2, 3).dominates(&round(1, 2, 4)));
+    }
+
+    #[test]
+    fn max_and_min() {
+        let a = round(1, 9, 3);
+        let b = round(4, 2, 3);
+        assert_eq!(a.max(&b), round(4, 9, 3));
+        assert_eq!(a.min(&b), round(1, 2, 3));
+        assert!(a.max(&b).dominates(&a) && a.max(&b).dominates(&b));
+        assert!(a.min(&b).fits_within(&a) && a.min(&b).fits_within(&b));
+    }
+
+    #[test]
+    fn missing_colors_are_zero() {
+        let sparse = Round::from_iter([("red", 3)]);
+        assert_eq!(sparse, round(3, 0, 0));
+        assert_eq!(sparse.get("blue"), 0);
+        assert!(round(3, 0, 0).fits_within(&sparse));
+        assert!(!round(3, 1, 0).fits_within(&sparse));
+    }
+
+    #[test]
+    fn any_colors() {
+        let game = build_game("Game 7: 2 yellow, 1 red; 4 purple, 1 yellow").unwrap();
+        assert_eq!(game.rounds[0].get("yellow"), 2);
+        assert_eq!(game.rounds[1].get("purple"), 4);
+
+        let bag = Round::from_iter([("red", 1), ("yellow", 2), ("purple", 4)]);
+        assert!(game.check_game(&bag));
+        assert!(!game.check_game(&round(12, 13, 14)));
+
+        let minimum = super::part_2::find_minimum_cubes(&game);
+        assert_eq!(minimum.power(&["red", "yellow", "purple"]), 8);
+        assert_eq!(minimum.power(&["red", "yellow", "purple", "blue"]), 0);
+    }
+
+    #[test]
+    fn colors_in_order_seen() {
+        let games = vec![
+            build_game("Game 1: 2 yellow, 1 red").unwrap(),
+            build_game("Game 2: 1 red; 4 purple").unwrap(),
+        ];
+        assert_eq!(colors(&games), vec!["yellow", "red", "purple"]);
+        assert_eq!(super::part_2::sum_of_game_powers(&games), 0);
+    }
+
+    #[test]
+    fn minimum_cubes_per_color() {
+        let game = build_game("Game 1: 1 red, 99 blue; 5 green; 3 red, 1 blue").unwrap();
+        assert_eq!(super::part_2::find_minimum_cubes(&game), round(3, 5, 99));
+    }
+
     mod part_1 {
         use super::super::part_1::*;
         use super::super::*;
+        use crate::fixtures::sample_input;
 
         fn get_total() -> Round {
-            Round {
-                red: 12,
-                green: 13,
-                blue: 14,
-            }
+            Round::from_iter([("red", 12), ("green", 13), ("blue", 14)])
         }
 
         #[test]
         fn sample() {
-            let input = vec![
-                "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".to_string(),
-                "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue".to_string(),
-                "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"
-                    .to_string(),
-                "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"
-                    .to_string(),
-                "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green".to_string(),
-            ];
-
-            let result = sum_of_invalid_games(&input, &get_total());
+            let result = sum_of_invalid_games(&sample_input(), &get_total());
             assert_eq!(result, 8);
         }
 
@@ -144,7 +448,7 @@ mod tests {
             let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".to_string();
             let game = build_game(&input).unwrap();
             let total = get_total();
-            assert_eq!(game.check_game(&total), true);
+            assert!(game.check_game(&total));
         }
 
         #[test]
@@ -152,26 +456,130 @@ mod tests {
             let input = "Game 1: 3 blue, 20 red; 1 red, 2 green, 6 blue; 2 green".to_string();
             let game = build_game(&input).unwrap();
             let total = get_total();
-            assert_eq!(game.check_game(&total), false);
+            assert!(!game.check_game(&total));
         }
     }
 
-    mod part_2 {
-        use super::super::part_2::*;
+    mod parse {
+        use super::super::*;
 
         #[test]
-        fn sample() {
+        fn bad_id() {
+            let error = build_game("Game x1: 3 blue").unwrap_err();
+            assert_eq!(error.column, 6);
+            assert_eq!(error.found, "x1");
+            assert_eq!(error.expected, "a number");
+        }
+
+        #[test]
+        fn missing_colon() {
+            let error = build_game("Game 1 3 blue").unwrap_err();
+            assert_eq!(error.column, 14);
+            assert_eq!(error.found, "");
+        }
+
+        #[test]
+        fn bad_colour() {
+            let error = build_game("Game 1: 3 blue; 4 bl3w").unwrap_err();
+            assert_eq!(error.column, 19);
+            assert_eq!(error.found, "bl3w");
+        }
+
+        #[test]
+        fn repeated_colour() {
+            let error = build_game("Game 1: 3 blue, 4 red, 1 blue").unwrap_err();
+            assert_eq!(error.column, 26);
+            assert_eq!(error.expected, "a color not already in this round");
+        }
+
+        #[test]
+        fn error_line_number() {
             let input = vec![
-                "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".to_string(),
-                "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue".to_string(),
-                "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"
-                    .to_string(),
-                "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"
-                    .to_string(),
-                "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green".to_string(),
+                "Game 1: 3 blue".to_string(),
+                "".to_string(),
+                "Game 3: 3 blue, four red".to_string(),
             ];
+            let error = parse_input(&input).unwrap_err();
+            assert_eq!(error.line, 3);
+            assert_eq!(error.column, 17);
+            assert_eq!(error.found, "four");
+        }
+    }
+
+    mod display {
+        use super::super::simulate::Rng;
+        use super::super::*;
+
+        fn color(rng: &mut Rng) -> String {
+            let letters = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZéß";
+            let letters = letters.chars().collect::<Vec<char>>();
+            (0..=rng.below(8))
+                .map(|_| letters[rng.below(letters.len())])
+                .collect()
+        }
 
-            let result = sum_of_power(&input);
+        fn game(rng: &mut Rng) -> Game {
+            let rounds = (0..=rng.below(6))
+                .map(|_| {
+                    let mut round = Round::new();
+                    for _ in 0..=rng.below(5) {
+                        let name = color(rng);
+                        if round.colors().all(|c| c != name) {
+                            round.set(&name, rng.below(1000));
+                        }
+                    }
+                    round
+                })
+                .collect();
+            Game {
+                id: rng.next_u64() as usize,
+                rounds,
+            }
+        }
+
+        #[test]
+        fn sample() {
+            let line = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
+            let game = line.parse::<Game>().unwrap();
+            assert_eq!(game.to_string(), line);
+            assert_eq!(game.rounds[1].to_string(), "5 blue, 4 red, 13 green");
+            assert_eq!(
+                "5 blue, 4 red, 13 green".parse::<Round>().unwrap(),
+                game.rounds[1]
+            );
+        }
+
+        #[test]
+        fn canonical_form() {
+            let game = "Game  7:3 blue ;  0 red,1 green".parse::<Game>().unwrap();
+            assert_eq!(game.to_string(), "Game 7: 3 blue; 0 red, 1 green");
+        }
+
+        #[test]
+        fn round_trip() {
+            let mut rng = Rng::new(2023);
+            for _ in 0..2000 {
+                let game = game(&mut rng);
+                let line = game.to_string();
+                let parsed = line.parse::<Game>().unwrap();
+
+                assert_eq!(parsed.to_string(), line);
+                assert_eq!(parsed.id, game.id);
+                assert_eq!(parsed.rounds.len(), game.rounds.len());
+                for (a, b) in parsed.rounds.iter().zip(&game.rounds) {
+                    assert!(a.iter().eq(b.iter()));
+                }
+            }
+        }
+    }
+
+    mod part_2 {
+        use super::super::part_2::*;
+        use crate::fixtures::sample_input;
+
+        #[test]
+        fn sample() {
+            let result = sum_of_power(&sample_input());
             assert_eq!(result, 2286);
         }
     }
@@ -1,32 +1,92 @@
-use day_2::part_1;
-use day_2::part_2;
+use aoc_common::{read_input, run, Args, Solution};
+use day_2::bag::{parse_bag, parse_bag_config, parse_sweep, sweep, sweep_table};
+use day_2::filter::{filter_games, parse_filter};
+use day_2::simulate::{simulate, write_games, DrawPolicy, Replacement};
+use day_2::Day2;
 use std::fs;
-use std::env;
+use std::io;
+use std::ops::RangeInclusive;
+use std::process;
 
 fn main() {
-    let args = env::args().collect::<Vec<String>>();
+    let args = Args::from_env().switches(&["without-replacement"]);
 
-    if args.len() < 2 {
-        println!("Please provide a filename");
+    let bag = if let Some(spec) = args.value("bag") {
+        parse_bag(spec).unwrap_or_else(|e| exit_with(&e.render("--bag")))
+    } else if let Some(config) = args.value("config") {
+        let contents = fs::read_to_string(config).expect("Something went wrong reading the file");
+        parse_bag_config(&contents).unwrap_or_else(|e| exit_with(&e.render(config)))
+    } else {
+        Day2::default().bag
+    };
+    let day = Day2 { bag };
+
+    if let Some(count) = args.value("simulate") {
+        let games = count
+            .parse::<usize>()
+            .unwrap_or_else(|_| exit_with("--simulate expects a number of games"));
+        let seed = args.value("seed").map_or(0, |seed| {
+            seed.parse::<u64>()
+                .unwrap_or_else(|_| exit_with("--seed expects a number"))
+        });
+        let mut policy = DrawPolicy::default();
+        if let Some(spec) = args.value("rounds") {
+            policy.rounds =
+                parse_span(spec).unwrap_or_else(|| exit_with("--rounds expects N or A..=B"));
+        }
+        if let Some(spec) = args.value("draw") {
+            policy.cubes_per_draw =
+                parse_span(spec).unwrap_or_else(|| exit_with("--draw expects N or A..=B"));
+        }
+        if args.flag("without-replacement") {
+            policy.replacement = Replacement::Without;
+        }
+        if day.bag.total() == 0 {
+            exit_with("cannot draw from an empty bag");
+        }
+        let games = simulate(&day.bag, &policy, games, seed);
+        write_games(&mut io::stdout().lock(), &games)
+            .expect("Something went wrong writing the games");
         return;
     }
 
-    let data = read_file(&args[1]);
-    let total = day_2::Round {
-        red: 12,
-        green: 13,
-        blue: 14,
+    let Some(path) = args.input_path() else {
+        return;
     };
 
-    println!("Part 1 -> {}", part_1::sum_of_invalid_games(&data, &total));
-    println!("Part 2 -> {}", part_2::sum_of_power(&data));
+    if let Some(spec) = args.value("sweep") {
+        let axes = parse_sweep(spec).unwrap_or_else(|e| exit_with(&e.render("--sweep")));
+        let input = read_input(&path, Day2::BLANK_LINES);
+        let games = day
+            .parse(&input)
+            .unwrap_or_else(|e| exit_with(&e.render(&path)));
+        print!("{}", sweep_table(&axes, &sweep(&games, &axes)));
+        return;
+    }
+
+    if let Some(spec) = args.value("filter") {
+        let filter = parse_filter(spec).unwrap_or_else(|e| exit_with(&e.render("--filter")));
+        let input = read_input(&path, Day2::BLANK_LINES);
+        let games = day
+            .parse(&input)
+            .unwrap_or_else(|e| exit_with(&e.render(&path)));
+        for game in filter_games(&games, &filter) {
+            println!("{}", game);
+        }
+        return;
+    }
+
+    run(&day, &path);
+}
+
+// Either a single count or an inclusive `A..=B`, both at least 1.
+fn parse_span(spec: &str) -> Option<RangeInclusive<usize>> {
+    let (low, high) = spec.split_once("..=").unwrap_or((spec, spec));
+    let (low, high) = (low.trim().parse().ok()?, high.trim().parse().ok()?);
+    (low >= 1 && low <= high).then_some(low..=high)
 }
 
-fn read_file(filename: &str) -> Vec<String> {
-    let contents = fs::read_to_string(filename)
-        .expect("Something went wrong reading the file");
-    contents
-        .split("\n")
-        .map(|s| s.to_string())
-        .collect::<Vec<String>>()
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
 }
@@ -0,0 +1,160 @@
+use crate::{Game, Round};
+pub use aoc_common::Rng;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replacement {
+    // Cubes go back in the bag after every round, as in the puzzle.
+    With,
+    // Cubes stay out once drawn, so the game ends early if the bag runs dry.
+    Without,
+}
+
+#[derive(Debug, Clone)]
+pub struct DrawPolicy {
+    pub replacement: Replacement,
+    pub rounds: RangeInclusive<usize>,
+    pub cubes_per_draw: RangeInclusive<usize>,
+}
+
+impl Default for DrawPolicy {
+    fn default() -> Self {
+        DrawPolicy {
+            replacement: Replacement::With,
+            rounds: 1..=6,
+            cubes_per_draw: 1..=20,
+        }
+    }
+}
+
+// Takes `count` cubes out of `bag` one at a time, each equally likely.
+fn draw(bag: &mut Round, count: usize, rng: &mut Rng) -> Round {
+    let mut hand = Round::new();
+    for _ in 0..count.min(bag.total()) {
+        let mut pick = rng.below(bag.total());
+        let (color, left) = bag
+            .iter()
+            .find(|(_, n)| {
+                if pick < *n {
+                    return true;
+                }
+                pick -= n;
+                false
+            })
+            .map(|(c, n)| (c.to_string(), n))
+            .unwrap();
+        bag.set(&color, left - 1);
+        hand.set(&color, hand.get(&color) + 1);
+    }
+    hand
+}
+
+pub fn simulate_game(id: usize, bag: &Round, policy: &DrawPolicy, rng: &mut Rng) -> Game {
+    let mut remaining = bag.clone();
+    let mut rounds = Vec::new();
+
+    for _ in 0..rng.in_range(&policy.rounds).max(1) {
+        if policy.replacement == Replacement::With {
+            remaining = bag.clone();
+        }
+        let count = rng.in_range(&policy.cubes_per_draw).max(1);
+        let hand = draw(&mut remaining, count, rng);
+        if hand.total() == 0 {
+            break;
+        }
+        rounds.push(hand);
+    }
+
+    Game { id, rounds }
+}
+
+// Games numbered from 1, the same seed always gives the same games. The bag
+// needs at least one cube so every game has a round to show.
+pub fn simulate(bag: &Round, policy: &DrawPolicy, games: usize, seed: u64) -> Vec<Game> {
+    assert!(bag.total() > 0, "cannot draw from an empty bag");
+
+    let mut rng = Rng::new(seed);
+    (1..=games)
+        .map(|id| simulate_game(id, bag, policy, &mut rng))
+        .collect()
+}
+
+pub fn write_games<W: Write>(out: &mut W, games: &[Game]) -> io::Result<()> {
+    for game in games {
+        writeln!(out, "{}", game)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_game;
+    use crate::infer::minimum_bag;
+
+    fn bag() -> Round {
+        Round::from_iter([("red", 12), ("green", 13), ("blue", 14), ("yellow", 2)])
+    }
+
+    #[test]
+    fn same_seed_same_games() {
+        let policy = DrawPolicy::default();
+        let first = simulate(&bag(), &policy, 20, 7);
+        let second = simulate(&bag(), &policy, 20, 7);
+        let other = simulate(&bag(), &policy, 20, 8);
+
+        let lines = |games: &[Game]| games.iter().map(Game::to_string).collect::<Vec<String>>();
+        assert_eq!(lines(&first), lines(&second));
+        assert_ne!(lines(&first), lines(&other));
+    }
+
+    #[test]
+    fn games_fit_the_bag() {
+        let policy = DrawPolicy::default();
+        let games = simulate(&bag(), &policy, 200, 1);
+
+        assert!(games.iter().all(|g| g.check_game(&bag())));
+        assert!(minimum_bag(&games).fits_within(&bag()));
+        assert!(games.iter().all(|g| {
+            !g.rounds.is_empty()
+                && g.rounds
+                    .iter()
+                    .all(|r| policy.cubes_per_draw.contains(&r.total()))
+        }));
+    }
+
+    #[test]
+    fn without_replacement_empties_bag() {
+        let policy = DrawPolicy {
+            replacement: Replacement::Without,
+            rounds: 10..=10,
+            cubes_per_draw: 4..=4,
+        };
+        let bag = Round::from_iter([("red", 5), ("blue", 5)]);
+        let games = simulate(&bag, &policy, 50, 3);
+
+        for game in games {
+            assert_eq!(game.rounds.len(), 3);
+            let drawn = game.rounds.iter().fold(Round::new(), |acc, r| {
+                Round::from_iter(["red", "blue"].map(|c| (c, acc.get(c) + r.get(c))))
+            });
+            assert_eq!(drawn, bag);
+        }
+    }
+
+    #[test]
+    fn lines_parse_back() {
+        let games = simulate(&bag(), &DrawPolicy::default(), 50, 11);
+        let mut out = Vec::new();
+        write_games(&mut out, &games).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        for (line, game) in text.lines().zip(&games) {
+            let parsed = build_game(line).unwrap();
+            assert_eq!(parsed.id, game.id);
+            assert_eq!(parsed.rounds, game.rounds);
+        }
+        assert_eq!(text.lines().count(), 50);
+    }
+}
@@ -0,0 +1,54 @@
+// Times both parts on generated schematics far bigger than the puzzle input.
+//
+//     cargo run --release -p day-3 --example bench
+use aoc_common::Rng;
+use day_3::edit::EditableSchematic;
+use day_3::{generate, parse_schematic, part_1, part_2};
+use std::time::Instant;
+
+fn main() {
+    let mut rng = Rng::new(2023);
+
+    for size in [140, 500, 1000, 2000, 4000] {
+        let input = generate::schematic(size, size, &mut rng);
+
+        let start = Instant::now();
+        let schematic = parse_schematic(&input).unwrap();
+        let parsed = start.elapsed();
+
+        let start = Instant::now();
+        let sum = part_1::sum_of_parts(&schematic.parts);
+        let first = start.elapsed();
+
+        let start = Instant::now();
+        let ratios = part_2::sum_of_gears(&schematic.parts);
+        let second = start.elapsed();
+
+        println!(
+            "{0}x{0}: {1} parts, parse {2:?}, part 1 {3:?} ({4}), part 2 {5:?} ({6})",
+            size,
+            schematic.parts.len(),
+            parsed,
+            first,
+            sum,
+            second,
+            ratios.map_or("too large".to_string(), |r| r.to_string())
+        );
+
+        let mut edit = EditableSchematic::new(&schematic);
+        let palette = ['.', '*', '#', '1', '7'];
+        let start = Instant::now();
+        for _ in 0..100_000 {
+            let (x, y) = (rng.below(size), rng.below(size));
+            edit.set(x, y, palette[rng.below(palette.len())]).unwrap();
+        }
+        println!(
+            "{0}x{0}: 100000 edits {1:?} ({2}, {3})",
+            size,
+            start.elapsed(),
+            edit.part_sum(),
+            edit.gear_sum()
+                .map_or("too large".to_string(), |r| r.to_string())
+        );
+    }
+}
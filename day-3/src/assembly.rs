@@ -0,0 +1,170 @@
+use crate::index::edges;
+use crate::{Part, PartNumber, Point};
+
+// A group of parts joined through symbols touching numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembly {
+    // Positions in `parts`, in reading order.
+    pub members: Vec<usize>,
+    // The sum of the numbers in it.
+    pub total: usize,
+    // The top left and bottom right cells it covers.
+    pub bounds: (Point, Point),
+    // How many of each symbol it has, by symbol.
+    pub symbols: Vec<(char, usize)>,
+}
+
+fn root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+// Every part falls in exactly one assembly, so numbers and symbols touching
+// nothing come back on their own. Assemblies are ordered by their first part.
+pub fn assemblies(parts: &[Part]) -> Vec<Assembly> {
+    let mut parents = (0..parts.len()).collect::<Vec<usize>>();
+    for (a, b) in edges(parts) {
+        let (a, b) = (root(&mut parents, a), root(&mut parents, b));
+        parents[a.max(b)] = a.min(b);
+    }
+
+    // The position in `groups` of each root's assembly.
+    let mut group_of: Vec<Option<usize>> = vec![None; parts.len()];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for i in 0..parts.len() {
+        let r = root(&mut parents, i);
+        match group_of[r] {
+            Some(group) => groups[group].push(i),
+            None => {
+                group_of[r] = Some(groups.len());
+                groups.push(vec![i]);
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|members| assembly(parts, members))
+        .collect()
+}
+
+fn assembly(parts: &[Part], members: Vec<usize>) -> Assembly {
+    let mut total = 0;
+    let mut symbols: Vec<(char, usize)> = Vec::new();
+    for &i in &members {
+        match parts[i].number {
+            PartNumber::Number(n) => total += n,
+            PartNumber::Symbol(c) => match symbols.iter_mut().find(|(s, _)| *s == c) {
+                Some((_, count)) => *count += 1,
+                None => symbols.push((c, 1)),
+            },
+            PartNumber::None => {}
+        }
+    }
+    symbols.sort();
+
+    let points = || members.iter().flat_map(|&i| parts[i].points.iter());
+    let low = Point::new(
+        points().map(|p| p.x).min().unwrap_or(0),
+        points().map(|p| p.y).min().unwrap_or(0),
+    );
+    let high = Point::new(
+        points().map(|p| p.x).max().unwrap_or(0),
+        points().map(|p| p.y).max().unwrap_or(0),
+    );
+
+    Assembly {
+        members,
+        total,
+        bounds: (low, high),
+        symbols,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, lines};
+    use crate::{generate, parse_input};
+
+    #[test]
+    fn sample() {
+        let parts = parse_input(&fixtures::sample()).unwrap();
+        let found = assemblies(&parts);
+
+        assert_eq!(found.len(), 8);
+        assert_eq!(found[0].total, 467 + 35);
+        assert_eq!(found[0].bounds, (Point::new(0, 0), Point::new(3, 2)));
+        assert_eq!(found[0].symbols, vec![('*', 1)]);
+        assert_eq!(found[1].total, 114);
+        assert!(found[1].symbols.is_empty());
+        let totals = found.iter().map(|a| a.total).collect::<Vec<usize>>();
+        assert_eq!(totals, vec![502, 114, 633, 617, 592, 58, 1353, 664]);
+    }
+
+    #[test]
+    fn joined_through_numbers() {
+        let parts = parse_input(&lines(&["*12#", "...3", "4..$"])).unwrap();
+        let found = assemblies(&parts);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].members, vec![0, 1, 2, 3, 5]);
+        assert_eq!(found[0].total, 15);
+        assert_eq!(found[0].symbols, vec![('#', 1), ('$', 1), ('*', 1)]);
+        assert_eq!(found[0].bounds, (Point::new(0, 0), Point::new(3, 2)));
+        assert_eq!(found[1].members, vec![4]);
+        assert_eq!(found[1].bounds, (Point::new(0, 2), Point::new(0, 2)));
+    }
+
+    // Groups built straight from `Point::is_neighbour` over every pair.
+    fn by_pairs(parts: &[Part]) -> Vec<Vec<usize>> {
+        let touches = |a: &Part, b: &Part| {
+            a.points
+                .iter()
+                .any(|p| b.points.iter().any(|q| p.is_neighbour(q)))
+        };
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for i in 0..parts.len() {
+            let linked = groups
+                .iter()
+                .enumerate()
+                .filter(|(_, g)| {
+                    g.iter().any(|&j| {
+                        matches!(parts[i].number, PartNumber::Number(_))
+                            != matches!(parts[j].number, PartNumber::Number(_))
+                            && touches(&parts[i], &parts[j])
+                    })
+                })
+                .map(|(k, _)| k)
+                .collect::<Vec<usize>>();
+
+            let mut merged = vec![i];
+            for &k in linked.iter().rev() {
+                merged.extend(groups.remove(k));
+            }
+            merged.sort();
+            groups.push(merged);
+        }
+        groups.sort();
+        groups
+    }
+
+    #[test]
+    fn matches_pairwise() {
+        let mut rng = aoc_common::Rng::new(24);
+        for size in [1, 3, 8, 25] {
+            for _ in 0..20 {
+                let parts = parse_input(&generate::schematic(size, size, &mut rng)).unwrap();
+                let mut found = assemblies(&parts)
+                    .into_iter()
+                    .map(|a| a.members)
+                    .collect::<Vec<Vec<usize>>>();
+                found.sort();
+                assert_eq!(found, by_pairs(&parts));
+            }
+        }
+    }
+}
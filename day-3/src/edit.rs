@@ -0,0 +1,416 @@
+use crate::gear::GearRule;
+use crate::token::{tokenize, Span, Token};
+use crate::{Part, PartNumber, Schematic};
+use aoc_common::{Grid, ParseError};
+use std::ops::Range;
+
+// A schematic that can be changed a cell at a time, keeping both answers up to
+// date. An edit only looks at the rows either side of it and the numbers it
+// touches, rather than the whole schematic.
+#[derive(Debug, Clone)]
+pub struct EditableSchematic {
+    grid: Grid<char>,
+    // The number covering each cell, as a position in `numbers`.
+    cells: Grid<Option<usize>>,
+    numbers: Vec<Option<Span>>,
+    free: Vec<usize>,
+    rule: GearRule,
+    part_sum: usize,
+    gear_sum: GearTotal,
+}
+
+// Gear ratios summed wider than a usize, so an edit can take them back out
+// exactly, with the gears whose ratio doesn't fit in a usize counted apart.
+#[derive(Debug, Default, Clone, Copy)]
+struct GearTotal {
+    sum: u128,
+    overflows: usize,
+}
+
+impl GearTotal {
+    fn add(&mut self, ratio: Option<usize>) {
+        match ratio {
+            Some(ratio) => self.sum += ratio as u128,
+            None => self.overflows += 1,
+        }
+    }
+
+    fn replace(&mut self, old: GearTotal, new: GearTotal) {
+        self.sum = self.sum - old.sum + new.sum;
+        self.overflows = self.overflows - old.overflows + new.overflows;
+    }
+}
+
+fn is_symbol(c: char) -> bool {
+    !c.is_ascii_digit() && c != '.'
+}
+
+impl EditableSchematic {
+    pub fn new(schematic: &Schematic) -> EditableSchematic {
+        EditableSchematic::with_rule(schematic, GearRule::default())
+    }
+
+    // As `new`, but with `gear_sum` following `rule` instead of the puzzle's.
+    pub fn with_rule(schematic: &Schematic, rule: GearRule) -> EditableSchematic {
+        let grid = schematic.grid.clone();
+        let mut edit = EditableSchematic {
+            cells: Grid::new(grid.width(), grid.height(), None),
+            grid,
+            numbers: Vec::new(),
+            free: Vec::new(),
+            rule,
+            part_sum: 0,
+            gear_sum: GearTotal::default(),
+        };
+
+        // The grid already parsed, so its numbers fit.
+        for token in tokenize(&edit.grid).unwrap() {
+            if let Token::Number(span) = token {
+                edit.insert(span);
+            }
+        }
+        edit.part_sum = edit
+            .numbers
+            .iter()
+            .flatten()
+            .map(|span| edit.number_value(span))
+            .sum();
+        let mut gear_sum = GearTotal::default();
+        for ((x, y), _) in edit.grid.iter() {
+            gear_sum.add(edit.gear_value(x, y));
+        }
+        edit.gear_sum = gear_sum;
+        edit
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.grid.get(x, y).copied()
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.grid.rows().map(|row| row.iter().collect()).collect()
+    }
+
+    // The part 1 answer.
+    pub fn part_sum(&self) -> usize {
+        self.part_sum
+    }
+
+    // The part 2 answer, under the schematic's gear rule. `None` if a gear
+    // ratio, or their sum, doesn't fit in a usize.
+    pub fn gear_sum(&self) -> Option<usize> {
+        if self.gear_sum.overflows > 0 {
+            return None;
+        }
+        usize::try_from(self.gear_sum.sum).ok()
+    }
+
+    // Writes `c` at `(x, y)`, which must be inside the schematic. Characters
+    // the parser would reject, and edits that would make a number too big for
+    // a `usize`, leave the schematic as it was.
+    pub fn set(&mut self, x: usize, y: usize, c: char) -> Result<(), ParseError> {
+        assert!(
+            self.grid.contains(x, y),
+            "position ({}, {}) is outside a {}x{} schematic",
+            x,
+            y,
+            self.width(),
+            self.height()
+        );
+        if self.grid[(x, y)] == c {
+            return Ok(());
+        }
+
+        // Numbers that could grow, shrink, split or join. After the edit the
+        // numbers in this stretch of the row are still inside it.
+        let old = (x.saturating_sub(1)..=x + 1)
+            .filter_map(|nx| self.cells.get(nx, y).copied().flatten())
+            .fold(Vec::new(), |mut ids, id| {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+                ids
+            });
+        let spans = old.iter().map(|&id| self.numbers[id].unwrap());
+        let start = spans.clone().map(|s| s.start).min().unwrap_or(x).min(x);
+        let end = spans.map(|s| s.end).max().unwrap_or(x).max(x);
+
+        // Only built for an error, an edit shouldn't have to copy the row.
+        let error = |expected: &str| {
+            let mut line = self.grid.rows().nth(y).unwrap().to_vec();
+            line[x] = c;
+            let line = line.into_iter().collect::<String>();
+            let offset = line.char_indices().nth(x).unwrap().0;
+            ParseError::new(&line, &line[offset..offset + c.len_utf8()], expected).on_line(y)
+        };
+        if c.is_whitespace() || c.is_control() {
+            return Err(error("a digit, `.` or a symbol"));
+        }
+        let stretch = (start..=end)
+            .map(|i| if i == x { c } else { self.grid[(i, y)] })
+            .collect::<Vec<char>>();
+        let new = tokenize(&Grid::from_rows(vec![stretch]).unwrap())
+            .map_err(|_| error("a number that fits in a usize"))?
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Number(span) => Some(Span {
+                    row: y,
+                    start: span.start + start,
+                    end: span.end + start,
+                    value: span.value,
+                }),
+                _ => None,
+            })
+            .collect::<Vec<Span>>();
+
+        let rows = y.saturating_sub(1)..(y + 2).min(self.height());
+        let columns = start.saturating_sub(1)..(end + 2).min(self.width());
+        let (parts, gears) = self.totals(rows.clone(), columns.clone());
+
+        for id in old {
+            self.remove(id);
+        }
+        self.grid[(x, y)] = c;
+        for span in new {
+            self.insert(span);
+        }
+
+        let (new_parts, new_gears) = self.totals(rows, columns);
+        self.part_sum = self.part_sum - parts + new_parts;
+        self.gear_sum.replace(gears, new_gears);
+        Ok(())
+    }
+
+    fn insert(&mut self, span: Span) {
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.numbers[id] = Some(span);
+                id
+            }
+            None => {
+                self.numbers.push(Some(span));
+                self.numbers.len() - 1
+            }
+        };
+        for x in span.columns() {
+            self.cells[(x, span.row)] = Some(id);
+        }
+    }
+
+    fn remove(&mut self, id: usize) {
+        let span = self.numbers[id].take().unwrap();
+        for x in span.columns() {
+            self.cells[(x, span.row)] = None;
+        }
+        self.free.push(id);
+    }
+
+    // Distinct numbers around a cell, by position in `numbers`.
+    fn numbers_around(&self, x: usize, y: usize) -> Vec<usize> {
+        let mut ids = Vec::new();
+        for (nx, ny) in self.grid.neighbours_8(x, y) {
+            if let Some(id) = self.cells[(nx, ny)] {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+
+    // What a number adds to part 1.
+    fn number_value(&self, span: &Span) -> usize {
+        let touches_symbol = span.columns().any(|x| {
+            self.grid
+                .neighbours_8(x, span.row)
+                .any(|(nx, ny)| is_symbol(self.grid[(nx, ny)]))
+        });
+        if touches_symbol {
+            span.value
+        } else {
+            0
+        }
+    }
+
+    // What a cell adds to part 2, its ratio if it is a gear. `None` if that
+    // doesn't fit in a usize.
+    fn gear_value(&self, x: usize, y: usize) -> Option<usize> {
+        let c = self.grid[(x, y)];
+        let part = Part {
+            number: PartNumber::Symbol(c),
+            points: Vec::new(),
+        };
+        if !is_symbol(c) || !self.rule.is_candidate(&part) {
+            return Some(0);
+        }
+        let numbers = self
+            .numbers_around(x, y)
+            .iter()
+            .map(|&id| self.numbers[id].unwrap().value)
+            .collect::<Vec<usize>>();
+        if !self.rule.neighbours.contains(&numbers.len()) {
+            return Some(0);
+        }
+        self.rule.reduction.apply(&numbers)
+    }
+
+    // Both answers counting only the gears in the given cells and the numbers
+    // with a digit in them.
+    fn totals(&self, rows: Range<usize>, columns: Range<usize>) -> (usize, GearTotal) {
+        let mut ids = Vec::new();
+        let mut gears = GearTotal::default();
+        for y in rows {
+            for x in columns.clone() {
+                if let Some(id) = self.cells[(x, y)] {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+                gears.add(self.gear_value(x, y));
+            }
+        }
+        let parts = ids
+            .iter()
+            .map(|&id| self.number_value(&self.numbers[id].unwrap()))
+            .sum();
+        (parts, gears)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gear::Reduction;
+    use crate::part_1::sum_of_part_numbers;
+    use crate::part_2::{sum_of_gear_ratios, sum_of_gears};
+    use crate::{fixtures, generate, parse_input, parse_schematic};
+    use aoc_common::Rng;
+
+    fn sample() -> EditableSchematic {
+        EditableSchematic::new(&parse_schematic(&fixtures::sample()).unwrap())
+    }
+
+    #[test]
+    fn starts_with_the_answers() {
+        let edit = sample();
+        assert_eq!((edit.part_sum(), edit.gear_sum()), (4361, Some(467835)));
+    }
+
+    #[test]
+    fn edits() {
+        let mut edit = sample();
+
+        // Breaking the first gear loses its ratio and both its numbers.
+        edit.set(3, 1, '.').unwrap();
+        assert_eq!(edit.part_sum(), 4361 - 467 - 35);
+        assert_eq!(edit.gear_sum(), Some(467835 - 16345));
+
+        // The 4 of 114 turning into a `*` leaves 11 touching it.
+        edit.set(7, 0, '*').unwrap();
+        assert_eq!(edit.lines()[0], "467..11*..");
+        assert_eq!(edit.part_sum(), 4361 - 467 - 35 + 11);
+
+        // Filling the gap joins 467 and 11 into one number.
+        for x in 3..5 {
+            edit.set(x, 0, '0').unwrap();
+        }
+        assert_eq!(edit.lines()[0], "4670011*..");
+        assert_eq!(edit.part_sum(), 4361 - 467 - 35 + 4670011);
+        assert_eq!(edit.part_sum(), sum_of_part_numbers(&edit.lines()));
+        assert_eq!(edit.gear_sum(), Some(sum_of_gear_ratios(&edit.lines())));
+    }
+
+    #[test]
+    fn rejected_edits() {
+        let mut edit = sample();
+        let error = edit.set(2, 3, ' ').unwrap_err();
+        assert_eq!((error.line, error.column), (4, 3));
+        assert_eq!(edit.get(2, 3), Some('.'));
+
+        let mut edit = EditableSchematic::new(
+            &parse_schematic(&["9999999999.9999999999".to_string()]).unwrap(),
+        );
+        let error = edit.set(10, 0, '9').unwrap_err();
+        assert_eq!(error.expected, "a number that fits in a usize");
+        assert_eq!(edit.get(10, 0), Some('.'));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside a 10x10 schematic")]
+    fn outside() {
+        let _ = sample().set(10, 0, '.');
+    }
+
+    #[test]
+    fn custom_rule() {
+        let rule = GearRule {
+            symbols: vec!['*', '#'],
+            neighbours: 1..=8,
+            reduction: Reduction::Sum,
+        };
+        let schematic = parse_schematic(&sample().lines()).unwrap();
+        let mut edit = EditableSchematic::with_rule(&schematic, rule.clone());
+        assert_eq!(edit.gear_sum(), rule.sum(&schematic.parts).ok());
+
+        // 633 only touches the `#`, which stops being a gear.
+        edit.set(6, 3, '$').unwrap();
+        assert_eq!(
+            edit.gear_sum(),
+            Some(rule.sum(&schematic.parts).unwrap() - 633)
+        );
+        assert_eq!(
+            edit.gear_sum(),
+            rule.sum(&parse_input(&edit.lines()).unwrap()).ok()
+        );
+    }
+
+    #[test]
+    fn gear_sum_overflow() {
+        let schematic = |row: &str| parse_schematic(&[row.to_string()]).unwrap();
+        let mut edit = EditableSchematic::new(&schematic("99999999999*.99999999999"));
+        assert_eq!(edit.gear_sum(), Some(0));
+        edit.set(12, 0, '9').unwrap();
+        assert_eq!(edit.gear_sum(), None);
+        edit.set(12, 0, '.').unwrap();
+        assert_eq!(edit.gear_sum(), Some(0));
+
+        // Each ratio fits, their sum doesn't.
+        let mut edit =
+            EditableSchematic::new(&schematic("4294967295*4294967295.4294967295*4294967295"));
+        assert_eq!(edit.gear_sum(), None);
+        edit.set(32, 0, '.').unwrap();
+        assert_eq!(edit.gear_sum(), Some(4294967295 * 4294967295));
+    }
+
+    #[test]
+    fn matches_full_recompute() {
+        let mut rng = Rng::new(25);
+        let palette = ['.', '.', '.', '*', '*', '#', '$', '0', '1', '5', '7', '9'];
+
+        for (width, height) in [(1, 1), (3, 3), (8, 8), (8, 30), (2, 20), (40, 3)] {
+            let input = generate::schematic(width, height, &mut rng);
+            let mut edit = EditableSchematic::new(&parse_schematic(&input).unwrap());
+
+            for _ in 0..400 {
+                let (x, y) = (rng.below(width), rng.below(height));
+                let c = palette[rng.below(palette.len())];
+                if edit.set(x, y, c).is_err() {
+                    continue;
+                }
+
+                let lines = edit.lines();
+                let parts = parse_input(&lines).unwrap();
+                assert_eq!(edit.part_sum(), sum_of_part_numbers(&lines), "{:#?}", lines);
+                assert_eq!(edit.gear_sum(), sum_of_gears(&parts).ok(), "{:#?}", lines);
+            }
+        }
+    }
+}
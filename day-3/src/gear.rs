@@ -0,0 +1,214 @@
+use crate::index::PartIndex;
+use crate::{Part, PartNumber, Point};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+pub type Reducer = Rc<dyn Fn(&[usize]) -> usize>;
+
+// How the numbers around a gear combine into its ratio.
+#[derive(Clone)]
+pub enum Reduction {
+    Product,
+    Sum,
+    Min,
+    Max,
+    Custom(Reducer),
+}
+
+impl Reduction {
+    // `None` if the result doesn't fit in a usize. `Min` and `Max` of no
+    // numbers are 0.
+    pub fn apply(&self, numbers: &[usize]) -> Option<usize> {
+        match self {
+            Reduction::Product => numbers
+                .iter()
+                .try_fold(1, |acc: usize, &n| acc.checked_mul(n)),
+            Reduction::Sum => numbers
+                .iter()
+                .try_fold(0, |acc: usize, &n| acc.checked_add(n)),
+            Reduction::Min => Some(numbers.iter().copied().min().unwrap_or(0)),
+            Reduction::Max => Some(numbers.iter().copied().max().unwrap_or(0)),
+            Reduction::Custom(f) => Some(f(numbers)),
+        }
+    }
+}
+
+impl fmt::Debug for Reduction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reduction::Product => write!(f, "Product"),
+            Reduction::Sum => write!(f, "Sum"),
+            Reduction::Min => write!(f, "Min"),
+            Reduction::Max => write!(f, "Max"),
+            Reduction::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+// A gear whose ratio doesn't fit in a usize, or that takes the sum of the
+// ratios past `usize::MAX`, by the position of its symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    pub at: Point,
+}
+
+// Which symbols count as gears, how many numbers must touch one, and how those
+// numbers give its ratio. The default is the puzzle's: a `*` next to exactly
+// two numbers, multiplied together.
+#[derive(Debug, Clone)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub neighbours: RangeInclusive<usize>,
+    pub reduction: Reduction,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            neighbours: 2..=2,
+            reduction: Reduction::Product,
+        }
+    }
+}
+
+impl GearRule {
+    pub fn is_candidate(&self, part: &Part) -> bool {
+        matches!(part.number, PartNumber::Symbol(c) if self.symbols.contains(&c))
+    }
+
+    // The ratio of `part` if it is a gear under this rule.
+    pub fn ratio(
+        &self,
+        part: &Part,
+        parts: &[Part],
+        index: &PartIndex,
+    ) -> Result<Option<usize>, Overflow> {
+        if !self.is_candidate(part) {
+            return Ok(None);
+        }
+        let numbers = index
+            .neighbours_where(part, parts, |n| matches!(n, PartNumber::Number(_)))
+            .map(|n| match n.number {
+                PartNumber::Number(n) => n,
+                _ => 0,
+            })
+            .collect::<Vec<usize>>();
+
+        if !self.neighbours.contains(&numbers.len()) {
+            return Ok(None);
+        }
+        match self.reduction.apply(&numbers) {
+            Some(ratio) => Ok(Some(ratio)),
+            None => Err(Overflow { at: part.points[0] }),
+        }
+    }
+
+    // Every gear as its position in `parts` with its ratio.
+    pub fn gears(
+        &self,
+        parts: &[Part],
+        index: &PartIndex,
+    ) -> Result<Vec<(usize, usize)>, Overflow> {
+        let mut gears = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            if let Some(ratio) = self.ratio(part, parts, index)? {
+                gears.push((i, ratio));
+            }
+        }
+        Ok(gears)
+    }
+
+    pub fn sum(&self, parts: &[Part]) -> Result<usize, Overflow> {
+        let index = PartIndex::new(parts);
+        self.gears(parts, &index)?
+            .iter()
+            .try_fold(0, |acc: usize, &(i, ratio)| {
+                acc.checked_add(ratio).ok_or(Overflow {
+                    at: parts[i].points[0],
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, parse_input};
+
+    fn sample() -> Vec<Part> {
+        parse_input(&fixtures::sample()).unwrap()
+    }
+
+    #[test]
+    fn default_rule() {
+        assert_eq!(GearRule::default().sum(&sample()), Ok(467835));
+    }
+
+    #[test]
+    fn reductions() {
+        let rule = |reduction| GearRule {
+            reduction,
+            ..GearRule::default()
+        };
+        assert_eq!(
+            rule(Reduction::Sum).sum(&sample()),
+            Ok(467 + 35 + 755 + 598)
+        );
+        assert_eq!(rule(Reduction::Min).sum(&sample()), Ok(35 + 598));
+        assert_eq!(rule(Reduction::Max).sum(&sample()), Ok(467 + 755));
+        let custom = Reduction::Custom(Rc::new(|numbers| numbers.len() * 100));
+        assert_eq!(rule(custom).sum(&sample()), Ok(400));
+    }
+
+    #[test]
+    fn symbols_and_neighbour_counts() {
+        let any_star = GearRule {
+            neighbours: 1..=8,
+            reduction: Reduction::Sum,
+            ..GearRule::default()
+        };
+        assert_eq!(any_star.sum(&sample()), Ok(467 + 35 + 617 + 755 + 598));
+
+        let hashes = GearRule {
+            symbols: vec!['#'],
+            neighbours: 3..=3,
+            reduction: Reduction::Sum,
+        };
+        assert_eq!(hashes.sum(&sample()), Ok(0));
+
+        let parts = parse_input(&fixtures::lines(&["1.2", ".#.", "..3", "4#."])).unwrap();
+        assert_eq!(hashes.sum(&parts), Ok(6));
+
+        let either = GearRule {
+            symbols: vec!['#', '$'],
+            neighbours: 1..=1,
+            ..GearRule::default()
+        };
+        assert_eq!(either.sum(&sample()), Ok(633 + 664));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(Reduction::Product.apply(&[99999999999, 99999999999]), None);
+        assert_eq!(Reduction::Sum.apply(&[usize::MAX, 1]), None);
+        assert_eq!(Reduction::Product.apply(&[]), Some(1));
+
+        let parts = parse_input(&fixtures::lines(&["99999999999*99999999999"])).unwrap();
+        let error = GearRule::default().sum(&parts).unwrap_err();
+        assert_eq!(error.at, Point::new(11, 0));
+        let sum = GearRule {
+            reduction: Reduction::Sum,
+            ..GearRule::default()
+        };
+        assert_eq!(sum.sum(&parts), Ok(199999999998));
+
+        // Each ratio fits, their sum doesn't.
+        let big = "4294967295";
+        let row = format!("{}*{}.{}*{}", big, big, big, big);
+        let parts = parse_input(&fixtures::lines(&[&row])).unwrap();
+        let error = GearRule::default().sum(&parts).unwrap_err();
+        assert_eq!(error.at, Point::new(32, 0));
+    }
+}
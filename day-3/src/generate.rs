@@ -0,0 +1,29 @@
+use aoc_common::Rng;
+
+const SYMBOLS: &[char] = &['*', '#', '+', '$', '/', '@', '%', '&', '=', '-'];
+
+// A random schematic with numbers of up to three digits and a scattering of
+// symbols, for tests and timing runs on inputs bigger than the puzzle's.
+pub fn schematic(width: usize, height: usize, rng: &mut Rng) -> Vec<String> {
+    (0..height)
+        .map(|_| {
+            let mut row = String::with_capacity(width);
+            while row.len() < width {
+                match rng.below(10) {
+                    0 | 1 => row.push(SYMBOLS[rng.below(SYMBOLS.len())]),
+                    2..=4 => {
+                        let digits = rng.in_range(&(1..=3)).min(width - row.len());
+                        for _ in 0..digits {
+                            row.push(char::from(b'0' + rng.below(10) as u8));
+                        }
+                        if row.len() < width {
+                            row.push('.');
+                        }
+                    }
+                    _ => row.push('.'),
+                }
+            }
+            row
+        })
+        .collect()
+}
@@ -0,0 +1,388 @@
+use crate::gear::GearRule;
+use crate::index::edges;
+use crate::render::{classify, Role};
+use crate::{Part, PartNumber, Schematic};
+
+type Rgb = [u8; 3];
+
+const BACKGROUND: Rgb = [250, 250, 250];
+const EDGE: Rgb = [90, 90, 90];
+
+fn rgb(role: Role) -> Rgb {
+    match role {
+        Role::PartNumber => [60, 170, 80],
+        Role::Isolated => [200, 60, 60],
+        Role::Gear(_) => [230, 190, 40],
+        Role::NotGear => [170, 80, 170],
+        Role::Symbol => [40, 160, 190],
+    }
+}
+
+fn hex([r, g, b]: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// The middle of a part in cells, so `(0.5, 0.5)` for a symbol in the corner.
+fn centre(part: &Part) -> (f64, f64) {
+    let n = part.points.len().max(1) as f64;
+    let x = part.points.iter().map(|p| p.x as f64 + 0.5).sum::<f64>() / n;
+    let y = part.points.iter().map(|p| p.y as f64 + 0.5).sum::<f64>() / n;
+    (x, y)
+}
+
+const CELL: usize = 12;
+
+// Numbers as rounded boxes over their digits and symbols as circles, colored
+// as in `render::explain`, with a line from each symbol to every number it
+// touches.
+pub fn svg(schematic: &Schematic, rule: &GearRule) -> String {
+    let parts = &schematic.parts;
+    let roles = classify(parts, rule);
+    let cell = CELL as f64;
+    let (width, height) = (
+        schematic.grid.width() * CELL,
+        schematic.grid.height() * CELL,
+    );
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    out.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        width,
+        height,
+        hex(BACKGROUND)
+    ));
+
+    out.push_str(&format!(
+        "<g stroke=\"{}\" stroke-width=\"1\">\n",
+        hex(EDGE)
+    ));
+    for (from, to) in edges(parts) {
+        let (x1, y1) = centre(&parts[from]);
+        let (x2, y2) = centre(&parts[to]);
+        out.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+            x1 * cell,
+            y1 * cell,
+            x2 * cell,
+            y2 * cell
+        ));
+    }
+    out.push_str("</g>\n");
+
+    out.push_str("<g font-family=\"monospace\" font-size=\"10\" text-anchor=\"middle\">\n");
+    for (part, role) in parts.iter().zip(&roles) {
+        let fill = hex(rgb(*role));
+        let (x, y) = centre(part);
+        match part.number {
+            PartNumber::Number(n) => {
+                let left = part.points.iter().map(|p| p.x).min().unwrap_or(0);
+                let top = part.points.first().map_or(0, |p| p.y);
+                out.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"{}\"/>\n",
+                    left * CELL + 1,
+                    top * CELL + 1,
+                    part.points.len() * CELL - 2,
+                    CELL - 2,
+                    fill
+                ));
+                out.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\">{}</text>\n",
+                    x * cell,
+                    y * cell + 3.5,
+                    n
+                ));
+            }
+            PartNumber::Symbol(c) => {
+                out.push_str(&format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                    x * cell,
+                    y * cell,
+                    cell / 2.0 - 1.0,
+                    fill
+                ));
+                out.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\">{}</text>\n",
+                    x * cell,
+                    y * cell + 3.5,
+                    escape(c)
+                ));
+            }
+            PartNumber::None => {}
+        }
+    }
+    out.push_str("</g>\n</svg>\n");
+    out
+}
+
+fn escape(c: char) -> String {
+    match c {
+        '&' => "&amp;".to_string(),
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '"' => "&quot;".to_string(),
+        _ => c.to_string(),
+    }
+}
+
+// An RGB raster, `scale` pixels to a cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Image {
+    fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    // Bresenham's line between two pixels.
+    fn line(&mut self, (x1, y1): (i64, i64), (x2, y2): (i64, i64), color: Rgb) {
+        let (dx, dy) = ((x2 - x1).abs(), -(y2 - y1).abs());
+        let (sx, sy) = ((x2 - x1).signum(), (y2 - y1).signum());
+        let (mut x, mut y, mut error) = (x1, y1, dx + dy);
+        loop {
+            self.set(x as usize, y as usize, color);
+            if (x, y) == (x2, y2) {
+                break;
+            }
+            let twice = 2 * error;
+            if twice >= dy {
+                error += dy;
+                x += sx;
+            }
+            if twice <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    // Binary PPM (`P6`).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+
+    // An 8 bit RGB PNG. The image data is stored without compression, which
+    // keeps this short and is fine for schematics the size of the puzzle's.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut zlib = vec![0x78, 0x01];
+        let mut blocks = raw.chunks(65535).peekable();
+        if blocks.peek().is_none() {
+            zlib.extend([1, 0, 0, 0xff, 0xff]);
+        }
+        while let Some(block) = blocks.next() {
+            let len = block.len() as u16;
+            zlib.push(blocks.peek().is_none() as u8);
+            zlib.extend(len.to_le_bytes());
+            zlib.extend((!len).to_le_bytes());
+            zlib.extend(block);
+        }
+        zlib.extend(adler32(&raw).to_be_bytes());
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut out = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        chunk(&mut out, b"IHDR", &header);
+        chunk(&mut out, b"IDAT", &zlib);
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend(kind);
+    out.extend(data);
+    let mut crc = kind.to_vec();
+    crc.extend(data);
+    out.extend(crc32(&crc).to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+// The same picture as `svg` without the text: numbers as filled boxes,
+// symbols as dots and the edges drawn underneath.
+pub fn raster(schematic: &Schematic, rule: &GearRule, scale: usize) -> Image {
+    let parts = &schematic.parts;
+    let roles = classify(parts, rule);
+    let scale = scale.max(1);
+    let (width, height) = (
+        schematic.grid.width() * scale,
+        schematic.grid.height() * scale,
+    );
+    let mut image = Image {
+        width,
+        height,
+        pixels: vec![BACKGROUND; width * height],
+    };
+    let inset = usize::from(scale >= 3);
+
+    let middle = |part: &Part| {
+        let (x, y) = centre(part);
+        ((x * scale as f64) as i64, (y * scale as f64) as i64)
+    };
+    for (from, to) in edges(parts) {
+        image.line(middle(&parts[from]), middle(&parts[to]), EDGE);
+    }
+
+    for (part, role) in parts.iter().zip(&roles) {
+        let color = rgb(*role);
+        let radius = scale as f64 / 2.0;
+        for point in &part.points {
+            let (left, top) = (point.x * scale, point.y * scale);
+            for y in inset..scale - inset {
+                for x in 0..scale {
+                    let inside = match part.number {
+                        PartNumber::Symbol(_) => {
+                            let dx = x as f64 + 0.5 - scale as f64 / 2.0;
+                            let dy = y as f64 + 0.5 - scale as f64 / 2.0;
+                            dx * dx + dy * dy <= radius * radius
+                        }
+                        _ => true,
+                    };
+                    if inside {
+                        image.set(left + x, top + y, color);
+                    }
+                }
+            }
+        }
+    }
+
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, parse_schematic};
+
+    fn sample() -> Schematic {
+        parse_schematic(&fixtures::sample()).unwrap()
+    }
+
+    // Reads back the stored blocks written by `to_png`.
+    fn inflate_stored(mut zlib: &[u8]) -> Vec<u8> {
+        zlib = &zlib[2..];
+        let mut out = Vec::new();
+        loop {
+            let last = zlib[0] & 1 == 1;
+            let len = u16::from_le_bytes([zlib[1], zlib[2]]) as usize;
+            assert_eq!(!u16::from_le_bytes([zlib[3], zlib[4]]) as usize, len);
+            out.extend(&zlib[5..5 + len]);
+            zlib = &zlib[5 + len..];
+            if last {
+                break;
+            }
+        }
+        assert_eq!(zlib, adler32(&out).to_be_bytes());
+        out
+    }
+
+    #[test]
+    fn symbol_edges() {
+        let schematic = sample();
+        let edges = edges(&schematic.parts);
+        // Each of the six symbols touches these numbers: 2 + 1 + 1 + 1 + 1 + 2.
+        assert_eq!(edges.len(), 8);
+        let svg = svg(&schematic, &GearRule::default());
+        assert_eq!(svg.matches("<line ").count(), 8);
+        assert_eq!(svg.matches("<rect ").count(), 11);
+        assert_eq!(svg.matches("<circle ").count(), 6);
+        assert!(svg.contains("<text x=\"18\" y=\"9.5\">467</text>"));
+    }
+
+    #[test]
+    fn escapes_symbols() {
+        let schematic = parse_schematic(&["1&<".to_string()]).unwrap();
+        let svg = svg(&schematic, &GearRule::default());
+        assert!(svg.contains(">&amp;</text>") && svg.contains(">&lt;</text>"));
+    }
+
+    #[test]
+    fn ppm() {
+        let image = raster(&sample(), &GearRule::default(), 4);
+        assert_eq!((image.width, image.height), (40, 40));
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n40 40\n255\n"));
+        assert_eq!(ppm.len(), 13 + 40 * 40 * 3);
+
+        // The middle of the `4` in 467, and the empty corner.
+        assert_eq!(image.pixels[2 * 40 + 2], rgb(Role::PartNumber));
+        assert_eq!(image.pixels[39 * 40 + 39], BACKGROUND);
+    }
+
+    #[test]
+    fn png() {
+        let image = raster(&sample(), &GearRule::default(), 3);
+        let png = image.to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let mut rest = &png[8..];
+        let mut chunks = Vec::new();
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&rest[4..8 + len]));
+            chunks.push((kind.to_vec(), data.to_vec()));
+            rest = &rest[12 + len..];
+        }
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].0, b"IHDR");
+        assert_eq!(&chunks[0].1[..8], &[0, 0, 0, 30, 0, 0, 0, 30]);
+        let raw = inflate_stored(&chunks[1].1);
+        assert_eq!(raw.len(), 30 * (30 * 3 + 1));
+        let pixels = raw
+            .chunks(91)
+            .flat_map(|row| row[1..].chunks(3).map(|p| [p[0], p[1], p[2]]))
+            .collect::<Vec<Rgb>>();
+        assert_eq!(pixels, image.pixels);
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{Part, PartNumber, Point};
+use aoc_common::Grid;
+
+// Which part, by its position in the list, covers each cell of the schematic.
+// Looking around a part then only touches the cells next to it rather than
+// every other part.
+#[derive(Debug, Clone)]
+pub struct PartIndex {
+    cells: Grid<Option<usize>>,
+}
+
+impl PartIndex {
+    pub fn new(parts: &[Part]) -> PartIndex {
+        let points = || parts.iter().flat_map(|p| p.points.iter());
+        let width = points().map(|p| p.x + 1).max().unwrap_or(0);
+        let height = points().map(|p| p.y + 1).max().unwrap_or(0);
+
+        let mut cells = Grid::new(width, height, None);
+        for (i, part) in parts.iter().enumerate() {
+            for point in &part.points {
+                cells[(point.x, point.y)] = Some(i);
+            }
+        }
+        PartIndex { cells }
+    }
+
+    pub fn part_at(&self, point: &Point) -> Option<usize> {
+        self.cells.get(point.x, point.y).copied().flatten()
+    }
+
+    // The other parts touching `part`, including diagonally, each given once
+    // in the order first found.
+    pub fn neighbours(&self, part: &Part) -> Vec<usize> {
+        let own = part.points.first().and_then(|p| self.part_at(p));
+        let mut found = Vec::new();
+
+        for point in &part.points {
+            for (x, y) in self.cells.neighbours_8(point.x, point.y) {
+                if let Some(i) = self.cells[(x, y)] {
+                    if Some(i) != own && !found.contains(&i) {
+                        found.push(i);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    pub fn neighbours_where<'a>(
+        &'a self,
+        part: &Part,
+        parts: &'a [Part],
+        keep: impl Fn(&PartNumber) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a Part> + 'a {
+        self.neighbours(part)
+            .into_iter()
+            .map(move |i| &parts[i])
+            .filter(move |p| keep(&p.number))
+    }
+}
+
+// Each symbol with the numbers it touches, as positions in `parts`. Numbers
+// next to numbers and symbols next to symbols don't make an edge.
+pub fn edges(parts: &[Part]) -> Vec<(usize, usize)> {
+    let index = PartIndex::new(parts);
+    parts
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| matches!(p.number, PartNumber::Symbol(_)))
+        .flat_map(|(i, symbol)| {
+            index
+                .neighbours(symbol)
+                .into_iter()
+                .filter(|&j| matches!(parts[j].number, PartNumber::Number(_)))
+                .map(move |j| (i, j))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input;
+
+    #[test]
+    fn neighbours_once_each() {
+        let input = vec![
+            "467..".to_string(),
+            "..*..".to_string(),
+            ".35..".to_string(),
+        ];
+        let parts = parse_input(&input).unwrap();
+        let index = PartIndex::new(&parts);
+
+        assert_eq!(index.part_at(&Point::new(1, 0)), Some(0));
+        assert_eq!(index.part_at(&Point::new(3, 0)), None);
+        assert_eq!(index.part_at(&Point::new(9, 9)), None);
+        assert_eq!(index.neighbours(&parts[1]), vec![0, 2]);
+        assert_eq!(index.neighbours(&parts[0]), vec![1]);
+        assert_eq!(index.neighbours(&parts[2]), vec![1]);
+    }
+}
@@ -1,7 +1,21 @@
+use aoc_common::{BlankLines, Grid, ParseError};
+
+pub mod assembly;
+pub mod edit;
+pub mod gear;
+pub mod generate;
+pub mod image;
+pub mod index;
+pub mod render;
+pub mod token;
+
+use index::PartIndex;
+use token::{tokenize, Token};
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Point {
-    x: usize,
-    y: usize,
+    pub x: usize,
+    pub y: usize,
 }
 
 impl PartialEq for Point {
@@ -10,6 +24,8 @@ impl PartialEq for Point {
     }
 }
 
+impl Eq for Point {}
+
 impl<'a> Default for &'a Point {
     fn default() -> &'a Point {
         &Point { x: 0, y: 0 }
@@ -18,12 +34,10 @@ impl<'a> Default for &'a Point {
 
 impl Point {
     pub fn is_neighbour(&self, other: &Point) -> bool {
-        let x_diff = (self.x as i32 - other.x as i32).abs();
-        let y_diff = (self.y as i32 - other.y as i32).abs();
-        x_diff <= 1 && y_diff <= 1
+        self.x.abs_diff(other.x) <= 1 && self.y.abs_diff(other.y) <= 1
     }
 
-    pub fn is_neighbour_of_any(&self, others: &Vec<Point>) -> bool {
+    pub fn is_neighbour_of_any(&self, others: &[Point]) -> bool {
         others.iter().any(|other| self.is_neighbour(other))
     }
 
@@ -56,111 +70,182 @@ impl<'a> Default for &'a Part {
     }
 }
 
-pub fn parse_input(input: &Vec<String>) -> Vec<Part> {
-    let mut parts: Vec<Part> = Vec::new();
-    for (y, line) in input.iter().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            if c.is_digit(10) {
-                let number = c.to_digit(10).unwrap() as usize;
-                let mut last_part = parts.last().unwrap_or_default().clone();
-                let points = last_part.points.clone();
-                let point = Point::new(x, y);
-
-                if points.last().unwrap_or_default().is_neighbour(&point)
-                    && match last_part.number {
-                        PartNumber::Number(_) => true,
-                        _ => false,
-                    }
-                {
-                    last_part.points.push(point);
-                    let last_number = match last_part.number {
-                        PartNumber::Number(n) => n,
-                        _ => 0,
-                    };
-                    last_part.number = PartNumber::Number(last_number * 10 + number);
-                    parts.pop();
-                    parts.push(last_part);
-                } else {
-                    parts.push(Part {
-                        number: PartNumber::Number(number),
-                        points: vec![Point::new(x, y)],
-                    });
-                }
-            } else if c != '.' {
-                parts.push(Part {
-                    number: PartNumber::Symbol(c),
-                    points: vec![Point::new(x, y)],
-                });
-            }
-        }
-    }
-    parts
+// The schematic as written, alongside the numbers and symbols found in it.
+#[derive(Debug, Default, Clone)]
+pub struct Schematic {
+    pub grid: Grid<char>,
+    pub parts: Vec<Part>,
+}
+
+pub fn parse_schematic(input: &[String]) -> Result<Schematic, ParseError> {
+    let grid = Grid::parse(input, "a digit, `.` or a symbol", |c| {
+        (!c.is_whitespace() && !c.is_control()).then_some(c)
+    })?;
+
+    let parts = tokenize(&grid)?
+        .into_iter()
+        .map(|token| match token {
+            Token::Number(span) => Part {
+                number: PartNumber::Number(span.value),
+                points: span.columns().map(|x| Point::new(x, span.row)).collect(),
+            },
+            Token::Symbol {
+                row,
+                column,
+                symbol,
+            } => Part {
+                number: PartNumber::Symbol(symbol),
+                points: vec![Point::new(column, row)],
+            },
+        })
+        .collect();
+
+    Ok(Schematic { grid, parts })
+}
+
+pub fn parse_input(input: &[String]) -> Result<Vec<Part>, ParseError> {
+    parse_schematic(input).map(|schematic| schematic.parts)
 }
 
 pub mod part_1 {
     use super::*;
 
-    pub fn number_has_neighbor_symbol(number: &Part, symbols: &Vec<Part>) -> bool {
-        number.points.iter().any(|point| {
-            point.is_neighbour_of_any(&symbols.iter().flat_map(|s| s.points.clone()).collect())
-        })
+    pub fn number_has_neighbor_symbol(number: &Part, symbols: &[Part]) -> bool {
+        let points = symbols
+            .iter()
+            .flat_map(|s| s.points.iter().copied())
+            .collect::<Vec<Point>>();
+        number
+            .points
+            .iter()
+            .any(|point| point.is_neighbour_of_any(&points))
     }
 
-    pub fn sum_of_part_numbers(input: &Vec<String>) -> usize {
-        let parts = parse_input(input);
-        let symbols: Vec<Part> = parts
+    pub fn sum_of_part_numbers(input: &[String]) -> usize {
+        sum_of_parts(&parse_input(input).unwrap())
+    }
+
+    pub fn sum_of_parts(parts: &[Part]) -> usize {
+        let index = PartIndex::new(parts);
+        parts
             .iter()
-            .filter(|p| match p.number {
-                PartNumber::Symbol(_) => true,
-                _ => false,
+            .filter(|p| {
+                matches!(p.number, PartNumber::Number(_))
+                    && index
+                        .neighbours_where(p, parts, |n| matches!(n, PartNumber::Symbol(_)))
+                        .next()
+                        .is_some()
             })
-            .map(|p| p.clone())
-            .collect();
-        parts.iter().filter(|p| match p.number { PartNumber::Number(_) => true, _ => false } && number_has_neighbor_symbol(p, &symbols)).map(|p| match p.number {
-            PartNumber::Number(n) => n,
-            _ => 0,
-        }).sum()
+            .map(|p| match p.number {
+                PartNumber::Number(n) => n,
+                _ => 0,
+            })
+            .sum()
     }
 }
 
 pub mod part_2 {
     use super::*;
 
-    pub fn gear_has_two_neighbor_number(gear: &Part, numbers: &Vec<Part>) -> bool {
+    pub fn gear_has_two_neighbor_number(gear: &Part, numbers: &[Part]) -> bool {
         numbers
             .iter()
-            .filter(|n| gear.points.iter().any(|point| point.is_neighbour_of_any(&n.points)))
+            .filter(|n| {
+                gear.points
+                    .iter()
+                    .any(|point| point.is_neighbour_of_any(&n.points))
+            })
             .count()
             == 2
     }
 
-    pub fn find_gear_ratio(gear: &Part, numbers: &Vec<Part>) -> usize {
-        numbers.iter().filter(|n| gear.points.iter().any(|point| point.is_neighbour_of_any(&n.points))).map(|n| match n.number {
-            PartNumber::Number(n) => n,
-            _ => 1,
-        }).product()
-    }
-
-    pub fn sum_of_gear_ratios(input: &Vec<String>) -> usize {
-        let parts = parse_input(input);
-        let numbers: Vec<Part> = parts
+    pub fn find_gear_ratio(gear: &Part, numbers: &[Part]) -> usize {
+        numbers
             .iter()
-            .filter(|p| match p.number {
-            PartNumber::Number(_) => true,
-            _ => false,
+            .filter(|n| {
+                gear.points
+                    .iter()
+                    .any(|point| point.is_neighbour_of_any(&n.points))
             })
-            .map(|p| p.clone())
-            .collect();
-        let gears: Vec<Part> = parts
-            .iter()
-            .filter(|p| match p.number {
-                PartNumber::Symbol('*') => true,
-                _ => false,
+            .map(|n| match n.number {
+                PartNumber::Number(n) => n,
+                _ => 1,
             })
-            .map(|p| p.clone())
-            .filter(|p| gear_has_two_neighbor_number(p, &numbers))
-            .collect();
-        gears.iter().map(|g| find_gear_ratio(g, &numbers)).sum()
+            .product()
+    }
+
+    pub fn sum_of_gear_ratios(input: &[String]) -> usize {
+        sum_of_gears(&parse_input(input).unwrap()).unwrap()
+    }
+
+    pub fn sum_of_gears(parts: &[Part]) -> Result<usize, gear::Overflow> {
+        gear::GearRule::default().sum(parts)
+    }
+}
+
+pub struct Day3;
+
+impl aoc_common::Solution for Day3 {
+    type Parsed = Schematic;
+    type Answer = usize;
+
+    const BLANK_LINES: BlankLines = BlankLines::Keep;
+
+    fn parse(&self, input: &[String]) -> Result<Schematic, ParseError> {
+        let schematic = parse_schematic(input)?;
+        // Part 2 needs every gear ratio, and their sum, to fit in a usize.
+        if let Err(overflow) = part_2::sum_of_gears(&schematic.parts) {
+            let Point { x, y } = overflow.at;
+            let line = schematic
+                .grid
+                .rows()
+                .nth(y)
+                .unwrap()
+                .iter()
+                .collect::<String>();
+            let (offset, c) = line.char_indices().nth(x).unwrap();
+            let token = &line[offset..offset + c.len_utf8()];
+            return Err(ParseError::new(
+                &line,
+                token,
+                "gear ratios that fit in a usize, as does their sum",
+            )
+            .on_line(y));
+        }
+        Ok(schematic)
+    }
+
+    fn part_1(&self, parsed: &Schematic) -> usize {
+        part_1::sum_of_parts(&parsed.parts)
+    }
+
+    // `parse` checked the ratios fit.
+    fn part_2(&self, parsed: &Schematic) -> usize {
+        part_2::sum_of_gears(&parsed.parts).unwrap()
+    }
+}
+
+// Inputs shared by the tests of every module.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    pub(crate) fn lines(rows: &[&str]) -> Vec<String> {
+        rows.iter().map(|r| r.to_string()).collect()
+    }
+
+    // The schematic from the puzzle description.
+    pub(crate) fn sample() -> Vec<String> {
+        lines(&[
+            "467..114..",
+            "...*......",
+            "..35..633.",
+            "......#...",
+            "617*......",
+            ".....+.58.",
+            "..592.....",
+            "......755.",
+            "...$.*....",
+            ".664.598..",
+        ])
     }
 }
 
@@ -196,25 +281,123 @@ mod tests {
         assert!(!point.is_neighbour_of_any(&others));
     }
 
+    #[test]
+    fn parse_rejects_whitespace() {
+        let input = vec!["467..114..".to_string(), "...*.. ...".to_string()];
+        let error = parse_input(&input).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 7);
+        assert_eq!(error.found, " ");
+    }
+
+    #[test]
+    fn parse_keeps_rows_apart() {
+        let input = vec!["5".to_string(), "3".to_string(), "*".to_string()];
+        let parts = parse_input(&input).unwrap();
+        assert_eq!(parts[0].number, PartNumber::Number(5));
+        assert_eq!(parts[1].number, PartNumber::Number(3));
+        assert_eq!(crate::part_2::sum_of_gears(&parts), Ok(0));
+        assert_eq!(crate::part_1::sum_of_parts(&parts), 3);
+    }
+
+    #[test]
+    fn parse_rejects_ragged_rows() {
+        let input = vec!["467..".to_string(), "...*".to_string()];
+        let error = parse_input(&input).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 5);
+        assert_eq!(error.expected, "a line 5 characters long");
+    }
+
+    #[test]
+    fn schematic_grid() {
+        let input = vec!["467..".to_string(), "...*.".to_string()];
+        let schematic = parse_schematic(&input).unwrap();
+        assert_eq!(schematic.grid.width(), 5);
+        assert_eq!(schematic.grid[(3, 1)], '*');
+        assert_eq!(schematic.parts.len(), 2);
+    }
+
+    // The old scans over every part, kept to check the indexed sums against.
+    fn linear_sums(parts: &[Part]) -> (usize, usize) {
+        let of = |keep: fn(&PartNumber) -> bool| {
+            parts
+                .iter()
+                .filter(|p| keep(&p.number))
+                .cloned()
+                .collect::<Vec<Part>>()
+        };
+        let (numbers, symbols) = (
+            of(|n| matches!(n, PartNumber::Number(_))),
+            of(|n| matches!(n, PartNumber::Symbol(_))),
+        );
+        let value = |p: &Part| match p.number {
+            PartNumber::Number(n) => n,
+            _ => 0,
+        };
+
+        let part_1 = numbers
+            .iter()
+            .filter(|n| crate::part_1::number_has_neighbor_symbol(n, &symbols))
+            .map(value)
+            .sum();
+        let part_2 = of(|n| *n == PartNumber::Symbol('*'))
+            .iter()
+            .filter(|g| crate::part_2::gear_has_two_neighbor_number(g, &numbers))
+            .map(|g| crate::part_2::find_gear_ratio(g, &numbers))
+            .sum();
+        (part_1, part_2)
+    }
+
+    #[test]
+    fn indexed_matches_linear() {
+        let mut rng = aoc_common::Rng::new(3);
+        for size in [1, 2, 5, 20, 60] {
+            for _ in 0..10 {
+                let input = generate::schematic(size, size, &mut rng);
+                let parts = parse_input(&input).unwrap();
+                let sums = (
+                    crate::part_1::sum_of_parts(&parts),
+                    crate::part_2::sum_of_gears(&parts).unwrap(),
+                );
+                assert_eq!(sums, linear_sums(&parts), "{:#?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_rejects_overflow() {
+        let input = vec!["99999999999999999999999".to_string()];
+        let error = parse_input(&input).unwrap_err();
+        assert_eq!(error.line, 1);
+        assert_eq!(error.expected, "a number that fits in a usize");
+    }
+
+    #[test]
+    fn parse_rejects_gear_overflow() {
+        use aoc_common::Solution;
+
+        let input = vec![
+            ".......................".to_string(),
+            "99999999999*99999999999".to_string(),
+        ];
+        assert!(parse_schematic(&input).is_ok());
+        let error = Day3.parse(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 12));
+        assert_eq!(error.found, "*");
+        assert_eq!(
+            error.expected,
+            "gear ratios that fit in a usize, as does their sum"
+        );
+    }
+
     mod part_1 {
         use super::super::part_1::*;
         use super::super::*;
 
         #[test]
         fn sample() {
-            let input = vec![
-                "467..114..".to_string(),
-                "...*......".to_string(),
-                "..35..633.".to_string(),
-                "......#...".to_string(),
-                "617*......".to_string(),
-                ".....+.58.".to_string(),
-                "..592.....".to_string(),
-                "......755.".to_string(),
-                "...$.*....".to_string(),
-                ".664.598..".to_string(),
-            ];
-            let result = sum_of_part_numbers(&input);
+            let result = sum_of_part_numbers(&crate::fixtures::sample());
 
             assert_eq!(result, 4361);
         }
@@ -222,7 +405,7 @@ mod tests {
         #[test]
         fn single_number_input() {
             let input = vec!["..592.....".to_string()];
-            let result = parse_input(&input);
+            let result = parse_input(&input).unwrap();
 
             println!("{:?}", result);
             assert_eq!(result.len(), 1);
@@ -241,7 +424,7 @@ mod tests {
         #[test]
         fn single_symbol_input() {
             let input = vec!["..$.......".to_string()];
-            let result = parse_input(&input);
+            let result = parse_input(&input).unwrap();
 
             println!("{:?}", result);
             assert_eq!(result.len(), 1);
@@ -256,7 +439,7 @@ mod tests {
         #[test]
         fn multi_symbol_input() {
             let input = vec!["..$....#..".to_string()];
-            let result = parse_input(&input);
+            let result = parse_input(&input).unwrap();
 
             println!("{:?}", result);
             assert_eq!(result.len(), 2);
@@ -277,7 +460,7 @@ mod tests {
         #[test]
         fn symbol_and_number_input() {
             let input = vec!["..$....4..".to_string()];
-            let result = parse_input(&input);
+            let result = parse_input(&input).unwrap();
 
             println!("{:?}", result);
             assert_eq!(result.len(), 2);
@@ -310,44 +493,29 @@ mod tests {
 
         #[test]
         fn sample() {
-            let input = vec![
-                "467..114..".to_string(),
-                "...*......".to_string(),
-                "..35..633.".to_string(),
-                "......#...".to_string(),
-                "617*......".to_string(),
-                ".....+.58.".to_string(),
-                "..592.....".to_string(),
-                "......755.".to_string(),
-                "...$.*....".to_string(),
-                ".664.598..".to_string(),
-            ];
-            let result = sum_of_gear_ratios(&input);
+            let result = sum_of_gear_ratios(&crate::fixtures::sample());
 
             assert_eq!(result, 467835);
         }
 
         #[test]
         fn single_gear_input() {
-            assert_eq!(
-                gear_has_two_neighbor_number(
-                    &Part {
-                        number: PartNumber::Symbol('*'),
-                        points: vec![Point::new(1, 0)]
+            assert!(gear_has_two_neighbor_number(
+                &Part {
+                    number: PartNumber::Symbol('*'),
+                    points: vec![Point::new(1, 0)]
+                },
+                &[
+                    Part {
+                        number: PartNumber::Number(2),
+                        points: vec![Point::new(0, 0)]
                     },
-                    &vec![
-                        Part {
-                            number: PartNumber::Number(2),
-                            points: vec![Point::new(0, 0)]
-                        },
-                        Part {
-                            number: PartNumber::Number(2),
-                            points: vec![Point::new(2, 0)]
-                        }
-                    ]
-                ),
-                true
-            );
+                    Part {
+                        number: PartNumber::Number(2),
+                        points: vec![Point::new(2, 0)]
+                    }
+                ]
+            ));
         }
 
         #[test]
@@ -358,7 +526,7 @@ mod tests {
                         number: PartNumber::Symbol('*'),
                         points: vec![Point::new(1, 0)]
                     },
-                    &vec![
+                    &[
                         Part {
                             number: PartNumber::Number(2),
                             points: vec![Point::new(0, 0)]
@@ -1,27 +1,49 @@
-use day_3::part_1;
-use day_3::part_2;
+use aoc_common::{print_result, read_input, Args, Solution};
+use day_3::gear::GearRule;
+use day_3::image::{raster, svg};
+use day_3::render::explain;
+use day_3::Day3;
 use std::fs;
-use std::env;
+use std::process;
 
 fn main() {
-    let args = env::args().collect::<Vec<String>>();
-
-    if args.len() < 2 {
-        println!("Please provide a filename");
+    let args = Args::from_env().switches(&["explain"]);
+    let Some(path) = args.input_path() else {
         return;
+    };
+    let rule = GearRule::default();
+
+    let input = read_input(&path, Day3::BLANK_LINES);
+    let schematic = Day3
+        .parse(&input)
+        .unwrap_or_else(|e| exit_with(&e.render(&path)));
+
+    if args.flag("explain") {
+        print!("{}", explain(&schematic, &rule));
     }
 
-    let data = read_file(&args[1]);
+    let scale = args.value("scale").map_or(4, |scale| {
+        scale
+            .parse::<usize>()
+            .unwrap_or_else(|_| exit_with("--scale expects a number"))
+    });
+    for format in ["svg", "ppm", "png"] {
+        let Some(out) = args.value(format) else {
+            continue;
+        };
+        let bytes = match format {
+            "svg" => svg(&schematic, &rule).into_bytes(),
+            "ppm" => raster(&schematic, &rule, scale).to_ppm(),
+            _ => raster(&schematic, &rule, scale).to_png(),
+        };
+        fs::write(out, bytes).unwrap_or_else(|e| exit_with(&format!("{}: {}", out, e)));
+    }
 
-    println!("Part 1 -> {}", part_1::sum_of_part_numbers(&data));
-    println!("Part 2 -> {}", part_2::sum_of_gear_ratios(&data));
+    print_result(1, Day3.part_1(&schematic));
+    print_result(2, Day3.part_2(&schematic));
 }
 
-fn read_file(filename: &str) -> Vec<String> {
-    let contents = fs::read_to_string(filename)
-        .expect("Something went wrong reading the file");
-    contents
-        .split("\n")
-        .map(|s| s.to_string())
-        .collect::<Vec<String>>()
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
 }
@@ -0,0 +1,178 @@
+use crate::gear::GearRule;
+use crate::index::PartIndex;
+use crate::{Part, PartNumber, Schematic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    // A number touching a symbol, counted in part 1.
+    PartNumber,
+    // A number with no symbol next to it.
+    Isolated,
+    // A gear with its ratio, `None` if that doesn't fit in a usize.
+    Gear(Option<usize>),
+    // A gear symbol that doesn't meet the rule.
+    NotGear,
+    Symbol,
+}
+
+impl Role {
+    fn color(&self) -> &'static str {
+        match self {
+            Role::PartNumber => "\x1b[32m",
+            Role::Isolated => "\x1b[31m",
+            Role::Gear(_) => "\x1b[1;33m",
+            Role::NotGear => "\x1b[35m",
+            Role::Symbol => "\x1b[36m",
+        }
+    }
+}
+
+const EMPTY: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+// The role of every part, in the same order as `parts`.
+pub fn classify(parts: &[Part], rule: &GearRule) -> Vec<Role> {
+    let index = PartIndex::new(parts);
+    parts
+        .iter()
+        .map(|part| match part.number {
+            PartNumber::Number(_) => {
+                let symbols =
+                    index.neighbours_where(part, parts, |n| matches!(n, PartNumber::Symbol(_)));
+                if symbols.count() > 0 {
+                    Role::PartNumber
+                } else {
+                    Role::Isolated
+                }
+            }
+            _ if rule.is_candidate(part) => match rule.ratio(part, parts, &index) {
+                Ok(Some(ratio)) => Role::Gear(Some(ratio)),
+                Ok(None) => Role::NotGear,
+                Err(_) => Role::Gear(None),
+            },
+            _ => Role::Symbol,
+        })
+        .collect()
+}
+
+// The schematic as written with each part colored by its role, the ratios of
+// the gears on each row after it, and a key at the bottom.
+pub fn explain(schematic: &Schematic, rule: &GearRule) -> String {
+    let parts = &schematic.parts;
+    let roles = classify(parts, rule);
+    let index = PartIndex::new(parts);
+    let mut out = String::new();
+
+    for (y, row) in schematic.grid.rows().enumerate() {
+        let mut current = "";
+        let mut ratios = Vec::new();
+
+        for (x, &c) in row.iter().enumerate() {
+            let part = index.part_at(&crate::Point::new(x, y));
+            let color = part.map_or(EMPTY, |i| roles[i].color());
+            if color != current {
+                out.push_str(RESET);
+                out.push_str(color);
+                current = color;
+            }
+            out.push(c);
+
+            if let Some(Role::Gear(ratio)) = part.map(|i| roles[i]) {
+                ratios.push(ratio.map_or("too large".to_string(), |r| r.to_string()));
+            }
+        }
+        out.push_str(RESET);
+        if !ratios.is_empty() {
+            out.push_str(&format!("  gear ratio {}", ratios.join(", ")));
+        }
+        out.push('\n');
+    }
+
+    let count = |role: fn(&Role) -> bool| roles.iter().filter(|r| role(r)).count();
+    out.push_str(&format!(
+        "{}part number{} {}  {}isolated{} {}  {}gear{} {}  {}not a gear{} {}  {}symbol{} {}\n",
+        Role::PartNumber.color(),
+        RESET,
+        count(|r| *r == Role::PartNumber),
+        Role::Isolated.color(),
+        RESET,
+        count(|r| *r == Role::Isolated),
+        Role::Gear(None).color(),
+        RESET,
+        count(|r| matches!(r, Role::Gear(_))),
+        Role::NotGear.color(),
+        RESET,
+        count(|r| *r == Role::NotGear),
+        Role::Symbol.color(),
+        RESET,
+        count(|r| *r == Role::Symbol),
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, parse_schematic};
+
+    fn sample() -> Schematic {
+        parse_schematic(&fixtures::sample()).unwrap()
+    }
+
+    fn strip(text: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn roles() {
+        let schematic = sample();
+        let roles = classify(&schematic.parts, &GearRule::default());
+        let of = |n: usize| {
+            let i = schematic
+                .parts
+                .iter()
+                .position(|p| p.number == PartNumber::Number(n))
+                .unwrap();
+            roles[i]
+        };
+        assert_eq!(of(467), Role::PartNumber);
+        assert_eq!(of(114), Role::Isolated);
+        assert_eq!(of(58), Role::Isolated);
+        assert_eq!(roles[2], Role::Gear(Some(16345)));
+        assert_eq!(roles.iter().filter(|r| **r == Role::NotGear).count(), 1);
+        assert_eq!(roles.iter().filter(|r| **r == Role::Symbol).count(), 3);
+    }
+
+    #[test]
+    fn text_is_kept() {
+        let text = explain(&sample(), &GearRule::default());
+        let plain = strip(&text);
+        let lines = plain.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines[0], "467..114..");
+        assert_eq!(lines[1], "...*......  gear ratio 16345");
+        assert_eq!(lines[8], "...$.*....  gear ratio 451490");
+        assert_eq!(
+            lines[10],
+            "part number 8  isolated 2  gear 2  not a gear 1  symbol 3"
+        );
+    }
+
+    #[test]
+    fn colors() {
+        let text = explain(&sample(), &GearRule::default());
+        let first = text.lines().next().unwrap();
+        assert!(first.starts_with("\x1b[0m\x1b[32m467\x1b[0m\x1b[2m..\x1b[0m\x1b[31m114"));
+        assert!(text.lines().nth(1).unwrap().contains("\x1b[1;33m*"));
+        assert!(text.lines().nth(4).unwrap().contains("\x1b[35m*"));
+    }
+}
@@ -0,0 +1,173 @@
+use aoc_common::{Grid, ParseError};
+
+// A run of digits along one row. `end` is the column of the last digit, so a
+// one digit number has `start == end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    pub value: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Number(Span),
+    Symbol {
+        row: usize,
+        column: usize,
+        symbol: char,
+    },
+}
+
+impl Span {
+    pub fn columns(&self) -> impl Iterator<Item = usize> {
+        self.start..=self.end
+    }
+}
+
+// Splits each row into numbers and symbols in reading order, skipping `.`.
+// A number never carries on past the end of its row, whatever is below it.
+pub fn tokenize(grid: &Grid<char>) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+
+    for (row, cells) in grid.rows().enumerate() {
+        let mut number: Option<Span> = None;
+
+        for (column, &c) in cells.iter().enumerate() {
+            if let Some(digit) = c.to_digit(10) {
+                let span = number.get_or_insert(Span {
+                    row,
+                    start: column,
+                    end: column,
+                    value: 0,
+                });
+                span.end = column;
+                span.value = span
+                    .value
+                    .checked_mul(10)
+                    .and_then(|n| n.checked_add(digit as usize))
+                    .ok_or_else(|| {
+                        let line = cells.iter().collect::<String>();
+                        let offset = line.char_indices().nth(column).unwrap().0;
+                        let token = &line[offset..offset + c.len_utf8()];
+                        ParseError::new(&line, token, "a number that fits in a usize").on_line(row)
+                    })?;
+                continue;
+            }
+
+            tokens.extend(number.take().map(Token::Number));
+            if c != '.' {
+                tokens.push(Token::Symbol {
+                    row,
+                    column,
+                    symbol: c,
+                });
+            }
+        }
+        tokens.extend(number.map(Token::Number));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::lines;
+
+    fn grid(rows: &[&str]) -> Grid<char> {
+        Grid::parse(&lines(rows), "a character", Some).unwrap()
+    }
+
+    fn numbers(rows: &[&str]) -> Vec<(usize, usize, usize, usize)> {
+        tokenize(&grid(rows))
+            .unwrap()
+            .into_iter()
+            .filter_map(|t| match t {
+                Token::Number(s) => Some((s.row, s.start, s.end, s.value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spans_and_symbols() {
+        let tokens = tokenize(&grid(&["467..*", "..35#."])).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(Span {
+                    row: 0,
+                    start: 0,
+                    end: 2,
+                    value: 467
+                }),
+                Token::Symbol {
+                    row: 0,
+                    column: 5,
+                    symbol: '*'
+                },
+                Token::Number(Span {
+                    row: 1,
+                    start: 2,
+                    end: 3,
+                    value: 35
+                }),
+                Token::Symbol {
+                    row: 1,
+                    column: 4,
+                    symbol: '#'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn digit_above_digit() {
+        assert_eq!(numbers(&["5", "3"]), vec![(0, 0, 0, 5), (1, 0, 0, 3)]);
+    }
+
+    #[test]
+    fn row_end_then_row_start() {
+        assert_eq!(numbers(&["..7", "2.."]), vec![(0, 2, 2, 7), (1, 0, 0, 2)]);
+        assert_eq!(numbers(&[".7", "2."]), vec![(0, 1, 1, 7), (1, 0, 0, 2)]);
+        assert_eq!(
+            numbers(&["..12", "34.."]),
+            vec![(0, 2, 3, 12), (1, 0, 1, 34)]
+        );
+    }
+
+    #[test]
+    fn stacked_numbers() {
+        assert_eq!(
+            numbers(&["123", "456", "789"]),
+            vec![(0, 0, 2, 123), (1, 0, 2, 456), (2, 0, 2, 789)]
+        );
+        assert_eq!(
+            numbers(&["1.2", ".3.", "4.5"]),
+            vec![
+                (0, 0, 0, 1),
+                (0, 2, 2, 2),
+                (1, 1, 1, 3),
+                (2, 0, 0, 4),
+                (2, 2, 2, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn character_columns() {
+        assert_eq!(numbers(&["é12"]), vec![(0, 1, 2, 12)]);
+    }
+
+    #[test]
+    fn overflow() {
+        let error = tokenize(&grid(&[
+            "1.......................",
+            ".99999999999999999999999",
+        ]))
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (2, 21));
+        assert_eq!(error.expected, "a number that fits in a usize");
+    }
+}
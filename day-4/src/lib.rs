@@ -1,3 +1,5 @@
+use aoc_common::{BlankLines, ParseError};
+
 #[derive(Debug)]
 pub struct Card {
     pub card_number: usize,
@@ -35,49 +37,54 @@ impl Card {
     }
 }
 
-pub fn parse_input(input: &Vec<String>) -> Vec<Card> {
-    let mut cards: Vec<Card> = Vec::new();
-    for line in input.iter() {
-        let mut parts = line.split(":");
-        let card_number = parts
-            .next()
-            .unwrap()
-            .split_at(4)
-            .1
-            .trim()
-            .parse::<usize>()
-            .unwrap();
-        let mut numbers = parts.next().unwrap().trim().split("|");
-        let winning_numbers = numbers
-            .next()
-            .unwrap()
-            .trim()
-            .split(" ")
-            .filter_map(|s| match s.parse::<usize>() {
-                Ok(n) => Some(n),
-                Err(_) => None,
-            })
-            .collect::<Vec<usize>>();
-        let playing_numbers = numbers
-            .next()
-            .unwrap()
-            .trim()
-            .split(" ")
-            .filter_map(|s| match s.parse::<usize>() {
-                Ok(n) => Some(n),
-                Err(_) => None,
-            })
-            .collect::<Vec<usize>>();
-        cards.push(Card::new(card_number, winning_numbers, playing_numbers))
-    }
-    cards
+fn parse_numbers(line: &str, numbers: &str) -> Result<Vec<usize>, ParseError> {
+    numbers
+        .split_whitespace()
+        .map(|s| {
+            s.parse::<usize>()
+                .map_err(|_| ParseError::new(line, s, "a number"))
+        })
+        .collect()
+}
+
+pub fn parse_card(line: &str) -> Result<Card, ParseError> {
+    let (head, body) = line
+        .split_once(":")
+        .ok_or_else(|| ParseError::end_of_line(line, "`:`"))?;
+    let card_number = head
+        .strip_prefix("Card")
+        .ok_or_else(|| ParseError::new(line, head, "`Card <number>`"))?
+        .trim();
+    let card_number = card_number
+        .parse::<usize>()
+        .map_err(|_| ParseError::new(line, card_number, "a card number"))?;
+    let (winning, playing) = body
+        .split_once("|")
+        .ok_or_else(|| ParseError::end_of_line(line, "`|`"))?;
+
+    Ok(Card::new(
+        card_number,
+        parse_numbers(line, winning)?,
+        parse_numbers(line, playing)?,
+    ))
+}
+
+pub fn parse_input(input: &[String]) -> Result<Vec<Card>, ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_card(line).map_err(|e| e.on_line(i)))
+        .collect()
 }
 
 pub mod part_1 {
     use super::*;
 
-    pub fn sum_of_points(input: &Vec<String>) -> usize {
-        let cards = parse_input(input);
+    pub fn sum_of_points(input: &[String]) -> usize {
+        sum_of_card_points(&parse_input(input).unwrap())
+    }
+
+    pub fn sum_of_card_points(cards: &[Card]) -> usize {
         cards.iter().map(|card| card.score()).sum::<usize>()
     }
 }
@@ -85,13 +92,16 @@ pub mod part_1 {
 pub mod part_2 {
     use super::*;
 
-    pub fn sum_of_cards(input: &Vec<String>) -> usize {
-        let cards = parse_input(input);
+    pub fn sum_of_cards(input: &[String]) -> usize {
+        count_cards(&parse_input(input).unwrap())
+    }
+
+    pub fn count_cards(cards: &[Card]) -> usize {
         let card_winning_counts = cards
             .iter()
             .map(|card| card.matches())
             .collect::<Vec<usize>>();
-        let mut counts: Vec<usize> = vec![1; input.len()];
+        let mut counts: Vec<usize> = vec![1; cards.len()];
 
         for card in card_winning_counts.iter().enumerate() {
             let (index, score) = card;
@@ -103,6 +113,27 @@ pub mod part_2 {
     }
 }
 
+pub struct Day4;
+
+impl aoc_common::Solution for Day4 {
+    type Parsed = Vec<Card>;
+    type Answer = usize;
+
+    const BLANK_LINES: BlankLines = BlankLines::Drop;
+
+    fn parse(&self, input: &[String]) -> Result<Vec<Card>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part_1(&self, parsed: &Vec<Card>) -> usize {
+        part_1::sum_of_card_points(parsed)
+    }
+
+    fn part_2(&self, parsed: &Vec<Card>) -> usize {
+        part_2::count_cards(parsed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     mod part_1 {
@@ -112,7 +143,7 @@ mod tests {
         #[test]
         fn parse_input_test() {
             let input = vec!["Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53".to_string()];
-            let result = parse_input(&input);
+            let result = parse_input(&input).unwrap();
 
             assert_eq!(result.len(), 1);
             assert_eq!(result[0].card_number, 1);
@@ -137,6 +168,26 @@ mod tests {
             assert_eq!(result, 13);
         }
 
+        #[test]
+        fn parse_bad_number() {
+            let input = vec![
+                "Card 1: 41 48 | 83 86".to_string(),
+                "Card 2: 41 4x | 83 86".to_string(),
+            ];
+            let error = parse_input(&input).unwrap_err();
+
+            assert_eq!(error.line, 2);
+            assert_eq!(error.column, 12);
+            assert_eq!(error.found, "4x");
+        }
+
+        #[test]
+        fn parse_missing_bar() {
+            let error = parse_card("Card 1: 41 48 83 86").unwrap_err();
+            assert_eq!(error.column, 20);
+            assert_eq!(error.expected, "`|`");
+        }
+
         #[test]
         fn zero_points() {
             let input = Card::new(
@@ -190,5 +241,22 @@ mod tests {
             let result = sum_of_cards(&input);
             assert_eq!(result, 30);
         }
+
+        #[test]
+        fn solution() {
+            use aoc_common::Solution;
+
+            let input = vec![
+                "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53".to_string(),
+                "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19".to_string(),
+                "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1".to_string(),
+                "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83".to_string(),
+                "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36".to_string(),
+                "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11".to_string(),
+            ];
+            let cards = super::super::Day4.parse(&input).unwrap();
+            assert_eq!(super::super::Day4.part_1(&cards), 13);
+            assert_eq!(super::super::Day4.part_2(&cards), 30);
+        }
     }
 }
@@ -1,28 +1,10 @@
-use day_4::part_1;
-use day_4::part_2;
-use std::fs;
-use std::env;
+use aoc_common::{input_path, run};
+use day_4::Day4;
 
 fn main() {
-    let args = env::args().collect::<Vec<String>>();
-
-    if args.len() < 2 {
-        println!("Please provide a filename");
+    let Some(path) = input_path() else {
         return;
-    }
-
-    let data = read_file(&args[1]);
-
-    println!("Part 1 -> {}", part_1::sum_of_points(&data));
-    println!("Part 2 -> {}", part_2::sum_of_cards(&data));
-}
+    };
 
-fn read_file(filename: &str) -> Vec<String> {
-    let contents = fs::read_to_string(filename)
-        .expect("Something went wrong reading the file");
-    contents
-        .split("\n")
-        .map(|s| s.to_string())
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<String>>()
+    run(&Day4, &path);
 }
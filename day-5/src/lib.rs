@@ -1,95 +1,480 @@
+use aoc_common::{BlankLines, ParseError};
+
 #[derive(Debug)]
 pub struct Input {
     pub seeds: Vec<usize>,
-    pub range_store: Vec<Vec<Range>>,
+    pub maps: Vec<Map>,
+}
+
+// One "<from>-to-<to> map:" section, an edge between two categories.
+#[derive(Debug, Clone)]
+pub struct Map {
+    pub from: String,
+    pub to: String,
+    pub ranges: Vec<Range>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeError {
+    Overflow,
+    Inverted { start: usize, end: usize },
+    WrongLength(usize),
+    Unpaired,
+}
+
+impl std::fmt::Display for RangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeError::Overflow => write!(f, "range does not fit in a usize"),
+            RangeError::Inverted { start, end } => {
+                write!(f, "range start {} is after its end {}", start, end)
+            }
+            RangeError::WrongLength(len) => write!(f, "expected 3 numbers, found {}", len),
+            RangeError::Unpaired => write!(f, "the last seed has no length"),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl std::error::Error for RangeError {}
+
+// A half open interval `start..end` of source values, along with where the
+// first of them lands in the destination. A plain interval is a range that
+// maps onto itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Range {
+    start: usize,
+    end: usize,
     destination_start: usize,
-    source_start: usize,
-    length: usize,
 }
 
 impl Range {
-    pub fn map_range(&self, range: Range) -> Option<Range> {
-        let start = self.map(range.source_start)?;
-        let end = self.map(range.source_start + range.length)?;
-        Some(Range::new(start, end - start, range.length))
+    pub fn new(
+        destination_start: usize,
+        source_start: usize,
+        length: usize,
+    ) -> Result<Self, RangeError> {
+        let end = source_start
+            .checked_add(length)
+            .ok_or(RangeError::Overflow)?;
+        destination_start
+            .checked_add(length)
+            .ok_or(RangeError::Overflow)?;
+        Ok(Self {
+            start: source_start,
+            end,
+            destination_start,
+        })
     }
 
-    pub fn map(&self, value: usize) -> Option<usize> {
-        if value < self.source_start {
-            return None;
+    pub fn interval(start: usize, end: usize) -> Result<Self, RangeError> {
+        if start > end {
+            return Err(RangeError::Inverted { start, end });
         }
-        if value > self.source_start + self.length {
+        Ok(Self {
+            start,
+            end,
+            destination_start: start,
+        })
+    }
+
+    pub fn from_vec(vec: Vec<usize>) -> Result<Self, RangeError> {
+        if vec.len() != 3 {
+            return Err(RangeError::WrongLength(vec.len()));
+        }
+        Self::new(vec[0], vec[1], vec[2])
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn destination_start(&self) -> usize {
+        self.destination_start
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    // The part of `self` that `other` also covers, still mapped the way `self` is.
+    pub fn intersect(&self, other: &Range) -> Option<Range> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        if start >= end {
             return None;
         }
-        Some(self.destination_start + (value - self.source_start))
+        Some(Range {
+            start,
+            end,
+            destination_start: self.destination_start + (start - self.start),
+        })
     }
 
-    pub fn from_vec(vec: Vec<usize>) -> Self {
-        Self {
-            destination_start: vec[0],
-            source_start: vec[1],
-            length: vec[2],
+    // The parts of `self` that `other` does not cover, at most one on each side.
+    pub fn difference(&self, other: &Range) -> Vec<Range> {
+        let Some(overlap) = self.intersect(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+        let mut result = Vec::new();
+        if self.start < overlap.start {
+            result.push(Range {
+                start: self.start,
+                end: overlap.start,
+                destination_start: self.destination_start,
+            });
         }
+        if overlap.end < self.end {
+            result.push(Range {
+                start: overlap.end,
+                end: self.end,
+                destination_start: overlap.destination_start + overlap.len(),
+            });
+        }
+        result
     }
 
-    pub fn new(destination_start: usize, source_start: usize, length: usize) -> Self {
-        Self {
-            destination_start,
-            source_start,
-            length,
+    // Moves the interval onto its destination, giving a plain interval.
+    pub fn translate(&self) -> Range {
+        Range {
+            start: self.destination_start,
+            end: self.destination_start + self.len(),
+            destination_start: self.destination_start,
+        }
+    }
+
+    // Swaps the source and destination sides.
+    pub fn invert(&self) -> Range {
+        Range {
+            start: self.destination_start,
+            end: self.destination_start + self.len(),
+            destination_start: self.start,
+        }
+    }
+
+    pub fn map_range(&self, range: &Range) -> Option<Range> {
+        Some(self.intersect(range)?.translate())
+    }
+
+    pub fn map(&self, value: usize) -> Option<usize> {
+        if !self.contains(value) {
+            return None;
         }
+        Some(self.destination_start + (value - self.start))
     }
 }
 
 impl Default for Range {
     fn default() -> Self {
         Self {
+            start: 0,
+            end: usize::MAX,
             destination_start: 0,
-            source_start: 0,
-            length: usize::MAX,
         }
     }
 }
 
-pub fn parse_input(input: &Vec<String>) -> Input {
-    let seeds = input[0]
-        .split(": ")
-        .nth(1)
-        .unwrap()
-        .split(" ")
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
-    let mut range_store: Vec<Vec<Range>> = vec![Vec::new(); 7];
-    let mut index = 0;
-
-    for line in input.iter().skip(3) {
+fn parse_numbers(line: &str, numbers: &str) -> Result<Vec<usize>, ParseError> {
+    numbers
+        .split_whitespace()
+        .map(|s| {
+            s.parse::<usize>()
+                .map_err(|_| ParseError::new(line, s, "a number"))
+        })
+        .collect()
+}
+
+fn parse_range(line: &str) -> Result<Range, ParseError> {
+    let numbers = parse_numbers(line, line)?;
+    if numbers.len() < 3 {
+        return Err(ParseError::end_of_line(line, "3 numbers"));
+    }
+    if numbers.len() > 3 {
+        let extra = line.split_whitespace().nth(3).unwrap();
+        return Err(ParseError::new(line, extra, "3 numbers"));
+    }
+    Range::from_vec(numbers)
+        .map_err(|e| ParseError::new(line, line, &format!("a valid range ({})", e)))
+}
+
+fn parse_header(line: &str) -> Result<Map, ParseError> {
+    let name = line
+        .strip_suffix(" map:")
+        .ok_or_else(|| ParseError::new(line, line, "`<from>-to-<to> map:`"))?;
+    let (from, to) = name
+        .split_once("-to-")
+        .ok_or_else(|| ParseError::new(line, name, "`<from>-to-<to>`"))?;
+
+    Ok(Map {
+        from: from.to_string(),
+        to: to.to_string(),
+        ranges: Vec::new(),
+    })
+}
+
+pub fn parse_input(input: &[String]) -> Result<Input, ParseError> {
+    let first = input.first().map(|s| s.as_str()).unwrap_or("");
+    let seeds = first
+        .strip_prefix("seeds:")
+        .ok_or_else(|| ParseError::new(first, first, "`seeds:`"))?;
+    let seeds = parse_numbers(first, seeds)?;
+    let mut maps: Vec<Map> = Vec::new();
+
+    for (i, line) in input.iter().enumerate().skip(1) {
         if line.is_empty() {
             continue;
         }
-        if line.chars().nth(0).unwrap().is_digit(10) {
-            let range = Range::from_vec(
-                line.split(" ")
-                    .map(|s| s.parse::<usize>().unwrap())
-                    .collect::<Vec<usize>>(),
-            );
-            range_store[index].push(range);
+        if line.chars().next().unwrap().is_ascii_digit() {
+            let range = parse_range(line).map_err(|e| e.on_line(i))?;
+            match maps.last_mut() {
+                Some(map) => map.ranges.push(range),
+                None => {
+                    return Err(
+                        ParseError::new(line, line, "a `<from>-to-<to> map:` header").on_line(i),
+                    )
+                }
+            }
         } else {
-            range_store[index].push(Range::default());
-            index += 1;
+            maps.push(parse_header(line).map_err(|e| e.on_line(i))?);
+        }
+    }
+    for map in maps.iter_mut() {
+        map.ranges.push(Range::default());
+    }
+
+    Ok(Input { seeds, maps })
+}
+
+// Splits `input` wherever one of `ranges` begins or ends. Each piece keeps its
+// source values from `input` and takes the mapping of the range covering it,
+// or maps onto itself if nothing does.
+pub fn split_range(input: &Range, ranges: &[Range]) -> Vec<Range> {
+    let mut pending = vec![Range::interval(input.start(), input.end()).unwrap()];
+    let mut result = Vec::new();
+
+    for range in ranges {
+        let mut leftover = Vec::new();
+
+        for piece in pending {
+            match range.intersect(&piece) {
+                Some(overlap) => {
+                    result.push(overlap);
+                    leftover.extend(piece.difference(range));
+                }
+                None => leftover.push(piece),
+            }
         }
+
+        pending = leftover;
+    }
+
+    result.extend(pending);
+    result
+}
+
+// Every interval of source values that `ranges` sends into `output`. A layer
+// need not be one to one, so a single output can come from several places.
+pub fn preimage(output: &Range, ranges: &[Range]) -> Vec<Range> {
+    split_range(&Range::default(), ranges)
+        .iter()
+        .filter_map(|piece| piece.invert().map_range(output))
+        .collect()
+}
+
+// A single sorted, non overlapping mapping standing in for a stack of layers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piecewise {
+    ranges: Vec<Range>,
+}
+
+impl Piecewise {
+    pub fn identity() -> Self {
+        Self {
+            ranges: vec![Range::default()],
+        }
+    }
+
+    pub fn ranges(&self) -> &[Range] {
+        &self.ranges
+    }
+
+    // Feeds the output of `self` through `layer`.
+    pub fn then(&self, layer: &[Range]) -> Self {
+        let mut ranges = self
+            .ranges
+            .iter()
+            .flat_map(|piece| {
+                split_range(&piece.translate(), layer)
+                    .into_iter()
+                    .map(move |q| {
+                        let start = piece.start() + (q.start() - piece.destination_start());
+                        Range {
+                            start,
+                            end: start + q.len(),
+                            destination_start: q.destination_start(),
+                        }
+                    })
+            })
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<Range>>();
+        ranges.sort_by_key(|r| r.start());
+
+        let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last)
+                    if last.end == range.start
+                        && last.destination_start + last.len() == range.destination_start =>
+                {
+                    last.end = range.end;
+                }
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged }
+    }
+
+    pub fn preimage(&self, output: &Range) -> Vec<Range> {
+        self.ranges
+            .iter()
+            .filter_map(|piece| piece.invert().map_range(output))
+            .collect()
+    }
+
+    pub fn lookup(&self, value: usize) -> Option<usize> {
+        let index = self.ranges.partition_point(|r| r.end() <= value);
+        self.ranges.get(index)?.map(value)
+    }
+}
+
+impl std::fmt::Display for Piecewise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for range in &self.ranges {
+            let target = range.translate();
+            writeln!(
+                f,
+                "{}..{} -> {}..{}",
+                range.start(),
+                range.end(),
+                target.start(),
+                target.end()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Input {
+    // The seeds read as pairs of a start and a length, as part 2 does.
+    pub fn seed_ranges(&self) -> Result<Vec<Range>, RangeError> {
+        if self.seeds.len() % 2 == 1 {
+            return Err(RangeError::Unpaired);
+        }
+        self.seeds
+            .chunks(2)
+            .map(|pair| Range::new(pair[0], pair[0], pair[1]))
+            .collect()
+    }
+
+    // The shortest chain of maps leading from one category to another.
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<&Map>> {
+        let mut previous: Vec<Option<usize>> = vec![None; self.maps.len()];
+        let mut seen = vec![from];
+        let mut queue = std::collections::VecDeque::from([(from, None)]);
+
+        while let Some((category, via)) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut via = via;
+                while let Some(index) = via {
+                    path.push(&self.maps[index]);
+                    via = previous[index];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for (index, map) in self.maps.iter().enumerate() {
+                if map.from == category && !seen.contains(&map.to.as_str()) {
+                    seen.push(&map.to);
+                    previous[index] = via;
+                    queue.push_back((&map.to, Some(index)));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn layers(&self, from: &str, to: &str) -> Option<Vec<&[Range]>> {
+        Some(
+            self.path(from, to)?
+                .iter()
+                .map(|map| map.ranges.as_slice())
+                .collect(),
+        )
+    }
+
+    pub fn seed_to_location(&self) -> Option<Vec<&[Range]>> {
+        self.layers("seed", "location")
+    }
+
+    // Values no range covers keep their number, as the puzzle says. Only
+    // `usize::MAX` can get past the identity range every map ends with.
+    pub fn convert(&self, value: usize, from: &str, to: &str) -> Option<usize> {
+        Some(self.layers(from, to)?.iter().fold(value, |acc, layer| {
+            layer.iter().find_map(|r| r.map(acc)).unwrap_or(acc)
+        }))
+    }
+
+    pub fn compose_between(&self, from: &str, to: &str) -> Option<Piecewise> {
+        Some(
+            self.layers(from, to)?
+                .iter()
+                .fold(Piecewise::identity(), |acc, layer| acc.then(layer)),
+        )
+    }
+
+    // Walks the layers backwards to find every seed that ends up in `locations`.
+    // No seed reaches a location if the maps never lead there.
+    pub fn seed_ranges_for(&self, locations: &Range) -> Vec<Range> {
+        let Some(layers) = self.seed_to_location() else {
+            return vec![];
+        };
+        layers.iter().rev().fold(vec![*locations], |acc, layer| {
+            acc.iter().flat_map(|r| preimage(r, layer)).collect()
+        })
+    }
+
+    pub fn seeds_for(&self, location: usize) -> Vec<usize> {
+        let Ok(location) = Range::new(location, location, 1) else {
+            return vec![];
+        };
+        self.seed_ranges_for(&location)
+            .iter()
+            .flat_map(|r| r.start()..r.end())
+            .collect()
+    }
+
+    pub fn compose(&self) -> Option<Piecewise> {
+        self.compose_between("seed", "location")
     }
-    range_store[index].push(Range::default());
-    Input { seeds, range_store }
 }
 
 pub mod part_1 {
     use super::*;
 
-    pub fn convert_number(input: usize, ranges: &Vec<Range>) -> Option<usize> {
+    pub fn convert_number(input: usize, ranges: &[Range]) -> Option<usize> {
         for range in ranges {
             if let Some(result) = range.map(input) {
                 return Some(result);
@@ -98,26 +483,31 @@ pub mod part_1 {
         None
     }
 
-    pub fn find_closest_seed(input: &Vec<String>) -> usize {
-        let input = parse_input(input);
+    pub fn find_closest_seed(input: &[String]) -> usize {
+        lowest_location(&parse_input(input).unwrap()).unwrap()
+    }
 
-        input
+    // `None` if the maps don't lead from `seed` to `location`.
+    pub fn lowest_location(input: &Input) -> Option<usize> {
+        let layers = input.seed_to_location()?;
+
+        let lowest = input
             .seeds
             .iter()
             .map(|s| {
-                input
-                    .range_store
+                layers
                     .iter()
-                    .fold(*s, |acc, ranges| convert_number(acc, ranges).unwrap())
+                    .fold(*s, |acc, ranges| convert_number(acc, ranges).unwrap_or(acc))
             })
-            .fold(usize::MAX, |acc, s| if s < acc { s } else { acc })
+            .fold(usize::MAX, |acc, s| if s < acc { s } else { acc });
+        Some(lowest)
     }
 }
 
 pub mod part_2 {
     use super::*;
 
-    pub fn convert_number(input: usize, ranges: &Vec<Range>) -> Option<usize> {
+    pub fn convert_number(input: usize, ranges: &[Range]) -> Option<usize> {
         for range in ranges {
             if let Some(result) = range.map(input) {
                 return Some(result);
@@ -126,59 +516,396 @@ pub mod part_2 {
         None
     }
 
-    // over counts by one and take forever to run :(
-    pub fn find_closest_seed(input: &Vec<String>) -> usize {
-        let input = parse_input(input);
-
-        // let lowest_location_range = input
-        //     .range_store
-        //     .iter()
-        //     .rev()
-        //     .nth(0)
-        //     .unwrap()
-        //     .iter()
-        //     .reduce(|acc, r| {
-        //         if r.destination_start < acc.source_start {
-        //             r
-        //         } else {
-        //             acc
-        //         }
-        //     })
-        //     .unwrap();
-        //
-        // let lowest_seed_range = input.range_store.iter().rev().skip(1).reduce(|acc, r| {
-        //     r.
-        // })
-
-        (0..(input.seeds.len() / 2)).fold(usize::MAX, |acc, i| {
-            let seed_range_start = input.seeds[i * 2];
-            let seed_range_len = input.seeds[i * 2 + 1];
-
-            let next = (seed_range_start..(seed_range_start + seed_range_len)).fold(
-                usize::MAX,
-                |acc, s| {
-                    let next = input
-                        .range_store
-                        .iter()
-                        .fold(s, |acc, ranges| convert_number(acc, ranges).unwrap());
-                    if next < acc {
-                        next
+    // Pushes an interval through one layer of ranges, splitting it wherever a
+    // range begins or ends. Anything no range covers is passed through unchanged.
+    pub fn convert_range(input: Range, ranges: &[Range]) -> Vec<Range> {
+        split_range(&input, ranges)
+            .iter()
+            .map(|r| r.translate())
+            .collect()
+    }
+
+    pub fn find_closest_seed(input: &[String]) -> usize {
+        lowest_location(&parse_input(input).unwrap()).unwrap()
+    }
+
+    // `None` if the seeds don't pair up into ranges, or the maps don't lead
+    // from `seed` to `location`.
+    pub fn lowest_location(input: &Input) -> Option<usize> {
+        let seed_ranges = input.seed_ranges().ok()?;
+
+        let lowest = input
+            .seed_to_location()?
+            .iter()
+            .fold(seed_ranges, |acc, ranges| {
+                acc.into_iter()
+                    .flat_map(|r| convert_range(r, ranges))
+                    .collect()
+            })
+            .iter()
+            .filter(|r| !r.is_empty())
+            .map(|r| r.start())
+            .min()
+            .unwrap_or(usize::MAX);
+        Some(lowest)
+    }
+
+    fn reaches_seed(input: &Input, seed_ranges: &[Range], start: usize, end: usize) -> bool {
+        let locations = Range::interval(start, end).unwrap();
+        input
+            .seed_ranges_for(&locations)
+            .iter()
+            .any(|r| seed_ranges.iter().any(|s| r.intersect(s).is_some()))
+    }
+
+    // Works backwards from the locations instead: checks ever larger windows
+    // upwards from 0, then narrows down on the first window any seed reaches.
+    pub fn find_closest_seed_reverse(input: &[String]) -> usize {
+        lowest_location_reverse(&parse_input(input).unwrap()).unwrap()
+    }
+
+    pub fn lowest_location_reverse(input: &Input) -> Option<usize> {
+        let seed_ranges = input.seed_ranges().ok()?;
+        input.seed_to_location()?;
+
+        let mut start: usize = 0;
+        let mut size: usize = 1;
+        loop {
+            let end = start.saturating_add(size);
+            if reaches_seed(input, &seed_ranges, start, end) {
+                let (mut low, mut high) = (start, end);
+                while high - low > 1 {
+                    let mid = low + (high - low) / 2;
+                    if reaches_seed(input, &seed_ranges, low, mid) {
+                        high = mid;
                     } else {
-                        acc
+                        low = mid;
                     }
-                },
-            );
-            if next < acc {
-                next
-            } else {
-                acc
+                }
+                return Some(low);
             }
-        })
+            if end == usize::MAX {
+                return Some(usize::MAX);
+            }
+            start = end;
+            size = size.saturating_mul(2);
+        }
+    }
+}
+
+pub struct Day5;
+
+// Part 2 reads the seeds as pairs of a start and a length, `parse_input` leaves
+// them alone so part 1 can still use an odd number of them.
+fn check_seed_pairs(line: &str, seeds: &[usize]) -> Result<(), ParseError> {
+    let numbers = line.strip_prefix("seeds:").unwrap_or(line);
+    let lengths = numbers.split_whitespace().skip(1).step_by(2);
+    for (pair, length) in seeds.chunks(2).zip(lengths) {
+        if let Err(e) = Range::new(pair[0], pair[0], pair[1]) {
+            let expected = format!("a length that keeps the range in a usize ({})", e);
+            return Err(ParseError::new(line, length, &expected));
+        }
+    }
+    if seeds.len() % 2 == 1 {
+        return Err(ParseError::end_of_line(
+            line,
+            "a length after the last seed",
+        ));
+    }
+    Ok(())
+}
+
+impl aoc_common::Solution for Day5 {
+    type Parsed = Input;
+    type Answer = usize;
+
+    const BLANK_LINES: BlankLines = BlankLines::Collapse;
+
+    fn parse(&self, input: &[String]) -> Result<Input, ParseError> {
+        let almanac = parse_input(input)?;
+        // The solvers need this path, other lookups between categories don't.
+        if almanac.path("seed", "location").is_none() {
+            let last = input.len() - 1;
+            return Err(ParseError::end_of_line(
+                &input[last],
+                "maps leading from `seed` to `location`",
+            )
+            .on_line(last));
+        }
+        check_seed_pairs(&input[0], &almanac.seeds)?;
+        Ok(almanac)
+    }
+
+    // `parse` checked the maps lead from `seed` to `location`.
+    fn part_1(&self, parsed: &Input) -> usize {
+        part_1::lowest_location(parsed).unwrap()
+    }
+
+    // `parse` checked the path, and that the seeds pair up.
+    fn part_2(&self, parsed: &Input) -> usize {
+        part_2::lowest_location(parsed).unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn map_excludes_end() {
+        let range = Range::new(50, 98, 2).unwrap();
+        assert_eq!(range.map(97), None);
+        assert_eq!(range.map(98), Some(50));
+        assert_eq!(range.map(99), Some(51));
+        assert_eq!(range.map(100), None);
+    }
+
+    #[test]
+    fn new_overflow() {
+        assert_eq!(Range::new(0, usize::MAX, 1), Err(RangeError::Overflow));
+        assert_eq!(Range::new(usize::MAX, 0, 1), Err(RangeError::Overflow));
+    }
+
+    #[test]
+    fn interval_inverted() {
+        assert_eq!(
+            Range::interval(5, 4),
+            Err(RangeError::Inverted { start: 5, end: 4 })
+        );
+    }
+
+    #[test]
+    fn from_vec_wrong_length() {
+        assert_eq!(Range::from_vec(vec![1, 2]), Err(RangeError::WrongLength(2)));
+    }
+
+    #[test]
+    fn intersect() {
+        let range = Range::new(50, 10, 20).unwrap();
+        let other = Range::interval(25, 40).unwrap();
+        assert_eq!(
+            range.intersect(&other),
+            Some(Range::new(65, 25, 5).unwrap())
+        );
+        assert_eq!(
+            other.intersect(&range),
+            Some(Range::interval(25, 30).unwrap())
+        );
+        assert_eq!(range.intersect(&Range::interval(30, 40).unwrap()), None);
+    }
+
+    #[test]
+    fn difference() {
+        let range = Range::interval(0, 10).unwrap();
+        assert_eq!(
+            range.difference(&Range::interval(3, 5).unwrap()),
+            vec![
+                Range::interval(0, 3).unwrap(),
+                Range::interval(5, 10).unwrap()
+            ]
+        );
+        assert_eq!(range.difference(&Range::interval(0, 20).unwrap()), vec![]);
+        assert_eq!(
+            range.difference(&Range::interval(20, 30).unwrap()),
+            vec![range]
+        );
+    }
+
+    #[test]
+    fn translate() {
+        let range = Range::new(50, 10, 20).unwrap();
+        assert_eq!(range.translate(), Range::interval(50, 70).unwrap());
+        assert_eq!(
+            range.map_range(&Range::interval(0, 15).unwrap()),
+            Some(Range::interval(50, 55).unwrap())
+        );
+    }
+
+    #[test]
+    fn compose_two_layers() {
+        let first = vec![Range::new(50, 10, 20).unwrap(), Range::default()];
+        let second = vec![Range::new(0, 60, 5).unwrap(), Range::default()];
+        let composed = Piecewise::identity().then(&first).then(&second);
+
+        assert_eq!(
+            composed.ranges(),
+            &[
+                Range::interval(0, 10).unwrap(),
+                Range::new(50, 10, 10).unwrap(),
+                Range::new(0, 20, 5).unwrap(),
+                Range::new(65, 25, 5).unwrap(),
+                Range::interval(30, 60).unwrap(),
+                Range::new(0, 60, 5).unwrap(),
+                Range::interval(65, usize::MAX).unwrap(),
+            ]
+        );
+        assert_eq!(composed.lookup(22), Some(2));
+        assert_eq!(composed.lookup(62), Some(2));
+        assert_eq!(composed.lookup(usize::MAX), None);
+    }
+
+    #[test]
+    fn compose_display() {
+        let composed = Piecewise::identity().then(&[Range::new(50, 10, 20).unwrap()]);
+        assert_eq!(
+            composed.to_string(),
+            format!(
+                "0..10 -> 0..10\n10..30 -> 50..70\n30..{0} -> 30..{0}\n",
+                usize::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn invert() {
+        let range = Range::new(50, 10, 20).unwrap();
+        assert_eq!(range.invert(), Range::new(10, 50, 20).unwrap());
+        assert_eq!(range.invert().invert(), range);
+    }
+
+    #[test]
+    fn preimage_many_to_one() {
+        let layer = vec![Range::new(0, 10, 5).unwrap(), Range::default()];
+        let mut result = preimage(&Range::interval(2, 12).unwrap(), &layer);
+        result.sort_by_key(|r| r.start());
+
+        assert_eq!(
+            result,
+            vec![
+                Range::interval(2, 10).unwrap(),
+                Range::interval(12, 15).unwrap(),
+            ]
+        );
+    }
+
+    fn shuffled_input() -> Vec<String> {
+        vec![
+            "seeds: 1 20".to_string(),
+            "".to_string(),
+            "soil-to-location map:".to_string(),
+            "100 0 10".to_string(),
+            "".to_string(),
+            "seed-to-soil map:".to_string(),
+            "5 0 5".to_string(),
+        ]
+    }
+
+    #[test]
+    fn parse_named_maps() {
+        let input = parse_input(&shuffled_input()).unwrap();
+        assert_eq!(input.maps.len(), 2);
+        assert_eq!(input.maps[0].from, "soil");
+        assert_eq!(input.maps[0].to, "location");
+        assert_eq!(input.maps[1].from, "seed");
+        assert_eq!(input.maps[1].to, "soil");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let input = vec!["seeds: 1 x".to_string()];
+        let error = parse_input(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 10));
+
+        let input = vec!["seeds: 1 2".to_string(), "1 2 3".to_string()];
+        let error = parse_input(&input).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.expected, "a `<from>-to-<to> map:` header");
+
+        let input = vec![
+            "seeds: 1 2".to_string(),
+            "seed-to-soil map:".to_string(),
+            "1 2".to_string(),
+        ];
+        let error = parse_input(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 4));
+
+        let input = vec![
+            "seeds: 1 2".to_string(),
+            "".to_string(),
+            "seed-soil map:".to_string(),
+        ];
+        let error = parse_input(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(error.found, "seed-soil");
+    }
+
+    #[test]
+    fn missing_location_path() {
+        use aoc_common::Solution;
+
+        let input = vec![
+            "seeds: 1 2".to_string(),
+            "seed-to-soil map:".to_string(),
+            "1 2 3".to_string(),
+        ];
+        let almanac = parse_input(&input).unwrap();
+        assert_eq!(almanac.convert(2, "seed", "soil"), Some(1));
+        assert!(almanac.seed_to_location().is_none());
+        assert!(almanac.compose().is_none());
+        assert_eq!(almanac.seeds_for(1), Vec::<usize>::new());
+        assert_eq!(crate::part_1::lowest_location(&almanac), None);
+        assert_eq!(crate::part_2::lowest_location(&almanac), None);
+        assert_eq!(crate::part_2::lowest_location_reverse(&almanac), None);
+
+        let error = Day5.parse(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 6));
+        assert_eq!(error.expected, "maps leading from `seed` to `location`");
+    }
+
+    #[test]
+    fn seed_pairs() {
+        use aoc_common::Solution;
+
+        let input = vec![
+            "seeds: 1 2 3".to_string(),
+            "seed-to-location map:".to_string(),
+            "10 1 5".to_string(),
+        ];
+        let almanac = parse_input(&input).unwrap();
+        assert_eq!(crate::part_1::lowest_location(&almanac), Some(10));
+        assert_eq!(almanac.seed_ranges(), Err(RangeError::Unpaired));
+        assert_eq!(crate::part_2::lowest_location(&almanac), None);
+        let error = Day5.parse(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 13));
+        assert_eq!(error.expected, "a length after the last seed");
+
+        let input = vec![
+            "seeds: 1 2 18446744073709551610 10".to_string(),
+            "seed-to-location map:".to_string(),
+            "10 1 5".to_string(),
+        ];
+        let almanac = parse_input(&input).unwrap();
+        assert_eq!(almanac.seed_ranges(), Err(RangeError::Overflow));
+        let error = Day5.parse(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 33));
+        assert_eq!(error.found, "10");
+    }
+
+    #[test]
+    fn path_between_categories() {
+        let input = parse_input(&shuffled_input()).unwrap();
+        let path = input.path("seed", "location").unwrap();
+        assert_eq!(
+            path.iter().map(|m| m.to.as_str()).collect::<Vec<&str>>(),
+            vec!["soil", "location"]
+        );
+        assert_eq!(input.path("location", "seed").map(|p| p.len()), None);
+        assert_eq!(input.path("soil", "soil").map(|p| p.len()), Some(0));
+    }
+
+    #[test]
+    fn convert_between_categories() {
+        let input = parse_input(&shuffled_input()).unwrap();
+        assert_eq!(input.convert(2, "seed", "soil"), Some(7));
+        assert_eq!(input.convert(2, "seed", "location"), Some(107));
+        assert_eq!(input.convert(12, "seed", "location"), Some(12));
+        assert_eq!(input.convert(2, "location", "seed"), None);
+        assert_eq!(
+            input.compose_between("seed", "location").unwrap().lookup(2),
+            Some(107)
+        );
+    }
+
     mod part_1 {
         use super::super::part_1::*;
 
@@ -222,14 +949,29 @@ mod tests {
             let result = find_closest_seed(&input);
             assert_eq!(result, 35);
         }
+
+        #[test]
+        fn largest_seed() {
+            let input = vec![
+                "seeds: 18446744073709551615 0".to_string(),
+                "seed-to-location map:".to_string(),
+                "3 0 1".to_string(),
+            ];
+            assert_eq!(find_closest_seed(&input), 3);
+            let input = crate::parse_input(&input).unwrap();
+            assert_eq!(
+                input.convert(usize::MAX, "seed", "location"),
+                Some(usize::MAX)
+            );
+        }
     }
 
     mod part_2 {
         use super::super::part_2::*;
+        use super::super::*;
 
-        #[test]
-        fn sample() {
-            let input = vec![
+        fn get_input() -> Vec<String> {
+            vec![
                 "seeds: 79 14 55 13".to_string(),
                 "".to_string(),
                 "seed-to-soil map:".to_string(),
@@ -263,9 +1005,145 @@ mod tests {
                 "humidity-to-location map:".to_string(),
                 "60 56 37".to_string(),
                 "56 93 4".to_string(),
-            ];
-            let result = find_closest_seed(&input);
+            ]
+        }
+
+        fn brute_force(input: &[String]) -> usize {
+            let input = parse_input(input).unwrap();
+            input
+                .seeds
+                .chunks(2)
+                .flat_map(|pair| pair[0]..(pair[0] + pair[1]))
+                .map(|s| {
+                    input
+                        .seed_to_location()
+                        .unwrap()
+                        .iter()
+                        .fold(s, |acc, ranges| convert_number(acc, ranges).unwrap())
+                })
+                .min()
+                .unwrap()
+        }
+
+        #[test]
+        fn sample() {
+            let result = find_closest_seed(&get_input());
             assert_eq!(result, 46);
         }
+
+        #[test]
+        fn compose_matches_layers() {
+            let input = parse_input(&get_input()).unwrap();
+            let composed = input.compose().unwrap();
+
+            for w in composed.ranges().windows(2) {
+                assert_eq!(w[0].end(), w[1].start());
+            }
+            for seed in 0..200 {
+                let expected = input
+                    .seed_to_location()
+                    .unwrap()
+                    .iter()
+                    .fold(seed, |acc, ranges| convert_number(acc, ranges).unwrap());
+                assert_eq!(composed.lookup(seed), Some(expected));
+            }
+        }
+
+        #[test]
+        fn normalized_input() {
+            let contents = get_input().join("\r\n") + "\r\n\r\n";
+            for blank_lines in [BlankLines::Keep, BlankLines::Collapse, BlankLines::Drop] {
+                let input = aoc_common::normalize(&contents, blank_lines);
+                assert_eq!(super::super::part_1::find_closest_seed(&input), 35);
+                assert_eq!(find_closest_seed(&input), 46);
+            }
+        }
+
+        #[test]
+        fn convert_soil_to_humidity() {
+            let input = parse_input(&get_input()).unwrap();
+            assert_eq!(input.convert(81, "soil", "humidity"), Some(78));
+            assert_eq!(input.convert(79, "seed", "location"), Some(82));
+        }
+
+        #[test]
+        fn seeds_for_location() {
+            let input = parse_input(&get_input()).unwrap();
+            assert!(input.seeds_for(46).contains(&82));
+            for seed in input.seeds_for(46) {
+                assert_eq!(input.compose().unwrap().lookup(seed), Some(46));
+            }
+        }
+
+        #[test]
+        fn composed_preimage_matches_layers() {
+            let input = parse_input(&get_input()).unwrap();
+            let locations = Range::interval(40, 60).unwrap();
+            let mut composed = input.compose().unwrap().preimage(&locations);
+            let mut layered = input.seed_ranges_for(&locations);
+            composed.sort_by_key(|r| r.start());
+            layered.sort_by_key(|r| r.start());
+
+            let count = |ranges: &[Range]| ranges.iter().map(|r| r.len()).sum::<usize>();
+            assert_eq!(count(&composed), count(&layered));
+            assert_eq!(
+                composed.iter().map(|r| r.start()).min(),
+                layered.iter().map(|r| r.start()).min()
+            );
+        }
+
+        #[test]
+        fn reverse_sample() {
+            let input = get_input();
+            assert_eq!(find_closest_seed_reverse(&input), 46);
+            assert_eq!(find_closest_seed_reverse(&input), find_closest_seed(&input));
+        }
+
+        #[test]
+        fn matches_brute_force() {
+            let input = get_input();
+            assert_eq!(find_closest_seed(&input), brute_force(&input));
+        }
+
+        fn interval(start: usize, end: usize) -> Range {
+            Range::interval(start, end).unwrap()
+        }
+
+        #[test]
+        fn range_inside() {
+            let ranges = vec![Range::new(50, 10, 20).unwrap()];
+            assert_eq!(
+                convert_range(interval(12, 15), &ranges),
+                vec![interval(52, 55)]
+            );
+        }
+
+        #[test]
+        fn range_outside() {
+            let ranges = vec![Range::new(50, 10, 20).unwrap()];
+            assert_eq!(
+                convert_range(interval(40, 45), &ranges),
+                vec![interval(40, 45)]
+            );
+        }
+
+        #[test]
+        fn range_split() {
+            let ranges = vec![
+                Range::new(50, 10, 20).unwrap(),
+                Range::new(0, 30, 5).unwrap(),
+            ];
+            let mut result = convert_range(interval(5, 40), &ranges);
+            result.sort_by_key(|r| r.start());
+            assert_eq!(
+                result,
+                vec![
+                    interval(0, 5),
+                    interval(5, 10),
+                    interval(35, 40),
+                    interval(50, 70)
+                ]
+            );
+        }
     }
 }